@@ -8,27 +8,19 @@ pub fn create_button_with_label(label: &str, provider: &CssProvider) -> Button {
     button
 }
 
-pub fn create_buttons_row(
+pub fn create_buttons_row<const N: usize>(
     hbox: &Box,
     provider: &CssProvider,
-    btn_labels: [&str; 4],
-) -> (Button, Button, Button, Button) {
+    btn_labels: [&str; N],
+) -> [Button; N] {
     // Create a horizontal box for the side-by-side buttons
 
     hbox.set_halign(gtk::Align::Center);
 
-    let mut buttons = [Button::new(), Button::new(), Button::new(), Button::new()];
     // Add buttons to the horizontal box
-    for (i, label) in btn_labels.iter().enumerate() {
+    btn_labels.map(|label| {
         let button = create_button_with_label(label, provider);
         hbox.pack_start(&button, false, false, 0);
-        buttons[i] = button;
-    }
-
-    (
-        buttons[0].clone(),
-        buttons[1].clone(),
-        buttons[2].clone(),
-        buttons[3].clone(),
-    )
+        button
+    })
 }
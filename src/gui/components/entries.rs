@@ -45,14 +45,27 @@ pub fn add_coordinate_widgets_box(
     (entries[0].clone(), entries[1].clone(), entries[2].clone())
 }
 
+/// Entry for the name of a section, at the top of the grid in place of a title
+fn attach_name_entry(grid: &Grid, provider: &CssProvider, name: &str) -> Entry {
+    let name_entry = create_entry_with_placeholder(name);
+    name_entry.set_tooltip_text(Some("Name, also used for the members of groups"));
+    grid.attach(&name_entry, 0, 0, 1, 1);
+    let style_context = name_entry.get_style_context();
+    style_context.add_provider(provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+    name_entry
+}
+
+/// Attach the name entry and an entry for each placeholder, labeled as a position followed by
+/// the size of the object.
+///
+/// Returns the name entry and the other entries.
 pub fn add_coordinate_widgets_grid(
     grid: &Grid,
     provider: &CssProvider,
     label_text: &str,
     placeholders: &[&str],
-) -> Vec<Entry> {
-    let label = Label::new(Some(label_text));
-    grid.attach(&label, 0, 0, 1, 1);
+) -> (Entry, Vec<Entry>) {
+    let name_entry = attach_name_entry(grid, provider, label_text);
 
     let mut entries = Vec::new();
     for (i, placeholder) in placeholders.iter().enumerate() {
@@ -81,7 +94,36 @@ pub fn add_coordinate_widgets_grid(
         style_context.add_provider(provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
         entries.push(entry);
     }
-    entries
+    (name_entry, entries)
+}
+
+/// Attach a label and an entry for each `(label, placeholder)` pair below the name entry in
+/// column 0.
+///
+/// Returns the name entry, the other entries and the first free row of the grid.
+pub fn add_labeled_entries_grid(
+    grid: &Grid,
+    provider: &CssProvider,
+    name: &str,
+    fields: &[(&str, &str)],
+) -> (Entry, Vec<Entry>, i32) {
+    let name_entry = attach_name_entry(grid, provider, name);
+
+    let mut entries = Vec::new();
+    for (i, (label_text, placeholder)) in fields.iter().enumerate() {
+        let top_label = i as i32 * 2 + 1;
+        let top_entry = top_label + 1;
+
+        let label = Label::new(Some(label_text));
+        grid.attach(&label, 0, top_label, 1, 1);
+
+        let entry = create_entry_with_placeholder(placeholder);
+        grid.attach(&entry, 0, top_entry, 1, 1);
+        let style_context = entry.get_style_context();
+        style_context.add_provider(provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+        entries.push(entry);
+    }
+    (name_entry, entries, fields.len() as i32 * 2 + 1)
 }
 
 pub fn add_resolution_box(vbox: &Box) -> (Entry, Entry) {
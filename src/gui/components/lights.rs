@@ -0,0 +1,223 @@
+use crate::gui::components::entries::*;
+use crate::gui::components::sections::add_section;
+use crate::gui::*;
+
+pub const FALLOFFS: [&str; 3] = ["Quadratic", "Linear", "None"];
+fn append_falloffs(falloff_selector: &ComboBoxText) {
+    for falloff in FALLOFFS {
+        falloff_selector.append_text(falloff);
+    }
+    falloff_selector.set_active(Some(0));
+}
+
+/// Color button that starts out white, since a black light does not do anything
+fn white_color_button() -> gtk::ColorButton {
+    let color_button = gtk::ColorButton::new();
+    let mut white = color_button.get_rgba();
+    white.red = 1.0;
+    white.green = 1.0;
+    white.blue = 1.0;
+    white.alpha = 1.0;
+    color_button.set_rgba(&white);
+    color_button
+}
+
+fn attach_falloff_selector(grid: &gtk::Grid, provider: &CssProvider, row: i32) -> ComboBoxText {
+    let falloff_label = gtk::Label::new(Some("Falloff"));
+    grid.attach(&falloff_label, 0, row, 1, 1);
+
+    let falloff_selector = ComboBoxText::new();
+    append_falloffs(&falloff_selector);
+    grid.attach(&falloff_selector, 0, row + 1, 1, 1);
+
+    let style_context = falloff_selector.get_style_context();
+    style_context.add_provider(provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+    falloff_selector
+}
+
+fn attach_color_button(grid: &gtk::Grid, row: i32) -> gtk::ColorButton {
+    let color_label = gtk::Label::new(Some("Color"));
+    grid.attach(&color_label, 0, row, 1, 1);
+
+    let color_button = white_color_button();
+    grid.attach(&color_button, 0, row + 1, 1, 1);
+    color_button
+}
+
+pub fn create_point_light_section(
+    app_state: Rc<RefCell<AppState>>,
+    flow_box: FlowBox,
+) -> gtk::Widget {
+    let provider = CssProvider::new();
+    provider
+        .load_from_path("src/gui/style.css")
+        .expect("Failed to load CSS");
+
+    let point_light_count = app_state.borrow_mut().next_section_number("point_light");
+    let unique_id = format!("point_light_{}", point_light_count); // Generate unique ID
+
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(5);
+    grid.set_widget_name(&unique_id);
+
+    let (name_entry, entries, row) = add_labeled_entries_grid(
+        &grid,
+        &provider,
+        &format!("Point Light {point_light_count}"),
+        &[
+            ("X pos", "0.0"),
+            ("Y pos", "5.0"),
+            ("Z pos", "0.0"),
+            ("Intensity", "20.0"),
+        ],
+    );
+
+    let falloff_selector = attach_falloff_selector(&grid, &provider, row);
+    let color_button = attach_color_button(&grid, row + 2);
+
+    let point_light_config = PointLightConfig {
+        id: Rc::new(RefCell::new(point_light_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
+        pos_x_entry: Rc::new(RefCell::new(entries[0].clone())),
+        pos_y_entry: Rc::new(RefCell::new(entries[1].clone())),
+        pos_z_entry: Rc::new(RefCell::new(entries[2].clone())),
+        intensity_entry: Rc::new(RefCell::new(entries[3].clone())),
+        falloff_selector: Rc::new(RefCell::new(falloff_selector)),
+        color_button: Rc::new(RefCell::new(color_button)),
+    };
+
+    let delete_button = gtk::Button::with_label("Delete");
+    grid.attach(&delete_button, 0, row + 4, 1, 1);
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        point_light_config,
+    );
+    grid.upcast::<gtk::Widget>()
+}
+
+pub fn create_spot_light_section(
+    app_state: Rc<RefCell<AppState>>,
+    flow_box: FlowBox,
+) -> gtk::Widget {
+    let provider = CssProvider::new();
+    provider
+        .load_from_path("src/gui/style.css")
+        .expect("Failed to load CSS");
+
+    let spot_light_count = app_state.borrow_mut().next_section_number("spot_light");
+    let unique_id = format!("spot_light_{}", spot_light_count); // Generate unique ID
+
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(5);
+    grid.set_widget_name(&unique_id);
+
+    let (name_entry, entries, row) = add_labeled_entries_grid(
+        &grid,
+        &provider,
+        &format!("Spot Light {spot_light_count}"),
+        &[
+            ("X pos", "0.0"),
+            ("Y pos", "5.0"),
+            ("Z pos", "0.0"),
+            ("X dir", "0.0"),
+            ("Y dir", "-1.0"),
+            ("Z dir", "0.0"),
+            ("Intensity", "20.0"),
+            ("Inner angle", "20.0"),
+            ("Outer angle", "30.0"),
+        ],
+    );
+
+    let falloff_selector = attach_falloff_selector(&grid, &provider, row);
+    let color_button = attach_color_button(&grid, row + 2);
+
+    let spot_light_config = SpotLightConfig {
+        id: Rc::new(RefCell::new(spot_light_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
+        pos_x_entry: Rc::new(RefCell::new(entries[0].clone())),
+        pos_y_entry: Rc::new(RefCell::new(entries[1].clone())),
+        pos_z_entry: Rc::new(RefCell::new(entries[2].clone())),
+        dir_x_entry: Rc::new(RefCell::new(entries[3].clone())),
+        dir_y_entry: Rc::new(RefCell::new(entries[4].clone())),
+        dir_z_entry: Rc::new(RefCell::new(entries[5].clone())),
+        intensity_entry: Rc::new(RefCell::new(entries[6].clone())),
+        inner_angle_entry: Rc::new(RefCell::new(entries[7].clone())),
+        outer_angle_entry: Rc::new(RefCell::new(entries[8].clone())),
+        falloff_selector: Rc::new(RefCell::new(falloff_selector)),
+        color_button: Rc::new(RefCell::new(color_button)),
+    };
+
+    let delete_button = gtk::Button::with_label("Delete");
+    grid.attach(&delete_button, 0, row + 4, 1, 1);
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        spot_light_config,
+    );
+    grid.upcast::<gtk::Widget>()
+}
+
+pub fn create_directional_light_section(
+    app_state: Rc<RefCell<AppState>>,
+    flow_box: FlowBox,
+) -> gtk::Widget {
+    let provider = CssProvider::new();
+    provider
+        .load_from_path("src/gui/style.css")
+        .expect("Failed to load CSS");
+
+    let directional_light_count = app_state
+        .borrow_mut()
+        .next_section_number("directional_light");
+    let unique_id = format!("directional_light_{}", directional_light_count); // Generate unique ID
+
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(5);
+    grid.set_widget_name(&unique_id);
+
+    let (name_entry, entries, row) = add_labeled_entries_grid(
+        &grid,
+        &provider,
+        &format!("Directional Light {directional_light_count}"),
+        &[
+            ("X dir", "-1.0"),
+            ("Y dir", "-1.0"),
+            ("Z dir", "-1.0"),
+            ("Intensity", "0.5"),
+        ],
+    );
+
+    let color_button = attach_color_button(&grid, row);
+
+    let directional_light_config = DirectionalLightConfig {
+        id: Rc::new(RefCell::new(directional_light_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
+        dir_x_entry: Rc::new(RefCell::new(entries[0].clone())),
+        dir_y_entry: Rc::new(RefCell::new(entries[1].clone())),
+        dir_z_entry: Rc::new(RefCell::new(entries[2].clone())),
+        intensity_entry: Rc::new(RefCell::new(entries[3].clone())),
+        color_button: Rc::new(RefCell::new(color_button)),
+    };
+
+    let delete_button = gtk::Button::with_label("Delete");
+    grid.attach(&delete_button, 0, row + 2, 1, 1);
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        directional_light_config,
+    );
+    grid.upcast::<gtk::Widget>()
+}
@@ -1,8 +1,18 @@
 use crate::gui::components::entries::*;
+use crate::gui::components::sections::add_section;
 use crate::gui::*;
 use rand::Rng;
 
-const MATERIALS: [&str; 3] = ["Diffusive", "Reflective", "Light"];
+const MATERIALS: [&str; 7] = [
+    "Diffusive",
+    "Reflective",
+    "Light",
+    "Checkerboard",
+    "Noise",
+    "Marble",
+    "Wood",
+];
+const PATTERNS: [&str; 4] = ["Checkerboard", "Noise", "Marble", "Wood"];
 fn append_materials(material_selector: &ComboBoxText) {
     for material in MATERIALS {
         material_selector.append_text(material);
@@ -10,18 +20,167 @@ fn append_materials(material_selector: &ComboBoxText) {
     material_selector.set_active(Some(0));
 }
 
-fn delete_component(flow_box: &FlowBox, id: String) {
+/// Emission strength and temperature entries, only editable when the "Light" material is chosen.
+///
+/// Takes four rows starting at `row`.
+fn attach_emission_entries(
+    grid: &gtk::Grid,
+    provider: &CssProvider,
+    material_selector: &ComboBoxText,
+    row: i32,
+) -> (Entry, Entry) {
+    let strength_label = gtk::Label::new(Some("Strength"));
+    grid.attach(&strength_label, 0, row, 1, 1);
+    let strength_entry = create_entry_with_placeholder("1.0");
+    grid.attach(&strength_entry, 0, row + 1, 1, 1);
+
+    // Empty means the color of the color button is used
+    let temperature_label = gtk::Label::new(Some("Temperature (K)"));
+    grid.attach(&temperature_label, 0, row + 2, 1, 1);
+    let temperature_entry = create_entry_with_placeholder("");
+    grid.attach(&temperature_entry, 0, row + 3, 1, 1);
+
+    for entry in [&strength_entry, &temperature_entry] {
+        let style_context = entry.get_style_context();
+        style_context.add_provider(provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+        entry.set_sensitive(false);
+    }
+
+    material_selector.connect_changed(
+        clone!(@strong strength_entry, @strong temperature_entry => move |selector| {
+            let is_light = selector.get_active_text().as_deref() == Some("Light");
+            strength_entry.set_sensitive(is_light);
+            temperature_entry.set_sensitive(is_light);
+        }),
+    );
+
+    (strength_entry, temperature_entry)
+}
+
+/// Scale entry and second color for the procedural materials, only editable when one of the
+/// `PATTERNS` is chosen. The pattern blends between the color button and the second color.
+///
+/// Takes four rows starting at `row`.
+fn attach_pattern_widgets(
+    grid: &gtk::Grid,
+    provider: &CssProvider,
+    material_selector: &ComboBoxText,
+    row: i32,
+) -> (Entry, gtk::ColorButton) {
+    let scale_label = gtk::Label::new(Some("Pattern scale"));
+    grid.attach(&scale_label, 0, row, 1, 1);
+    let scale_entry = create_entry_with_placeholder("1.0");
+    grid.attach(&scale_entry, 0, row + 1, 1, 1);
+    let style_context = scale_entry.get_style_context();
+    style_context.add_provider(provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+
+    let second_color_label = gtk::Label::new(Some("Second color"));
+    grid.attach(&second_color_label, 0, row + 2, 1, 1);
+    let second_color_button = gtk::ColorButton::new();
+    grid.attach(&second_color_button, 0, row + 3, 1, 1);
+
+    scale_entry.set_sensitive(false);
+    second_color_button.set_sensitive(false);
+
+    material_selector.connect_changed(
+        clone!(@strong scale_entry, @strong second_color_button => move |selector| {
+            let is_pattern = selector
+                .get_active_text()
+                .is_some_and(|material| PATTERNS.contains(&material.as_str()));
+            scale_entry.set_sensitive(is_pattern);
+            second_color_button.set_sensitive(is_pattern);
+        }),
+    );
+
+    (scale_entry, second_color_button)
+}
+
+/// The widgets every object section uses for its material
+struct MaterialWidgets {
+    material_selector: ComboBoxText,
+    color_button: gtk::ColorButton,
+    strength_entry: Entry,
+    temperature_entry: Entry,
+    pattern_scale_entry: Entry,
+    second_color_button: gtk::ColorButton,
+}
+
+/// Texture selector, color button, emission entries and pattern widgets.
+///
+/// Takes twelve rows starting at `row`.
+fn attach_material_widgets(grid: &gtk::Grid, provider: &CssProvider, row: i32) -> MaterialWidgets {
+    let material_label = gtk::Label::new(Some("Texture"));
+    grid.attach(&material_label, 0, row, 1, 1);
+
+    let material_selector = ComboBoxText::new();
+    append_materials(&material_selector);
+    grid.attach(&material_selector, 0, row + 1, 1, 1);
+
+    let style_context = material_selector.get_style_context();
+    style_context.add_provider(provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+
+    let color_label = gtk::Label::new(Some("Color"));
+    grid.attach(&color_label, 0, row + 2, 1, 1);
+
+    let color_button = gtk::ColorButton::new();
+    grid.attach(&color_button, 0, row + 3, 1, 1);
+
+    let (strength_entry, temperature_entry) =
+        attach_emission_entries(grid, provider, &material_selector, row + 4);
+    let (pattern_scale_entry, second_color_button) =
+        attach_pattern_widgets(grid, provider, &material_selector, row + 8);
+
+    MaterialWidgets {
+        material_selector,
+        color_button,
+        strength_entry,
+        temperature_entry,
+        pattern_scale_entry,
+        second_color_button,
+    }
+}
+
+/// Highlight the section called `id`, and only that one, and scroll the window to it
+pub fn select_section(flow_box: &FlowBox, id: &str) {
     for child in flow_box.get_children().iter() {
-        // Attempt to downcast the child to GtkFlowBoxChild
-        if let Some(flow_box_child) = child.downcast_ref::<gtk::FlowBoxChild>() {
-            if let Some(widget) = flow_box_child.get_child() {
-                let widget_name = widget.get_widget_name().to_string(); // Get the name of the widget inside the GtkFlowBoxChild
-                if widget_name == id {
-                    flow_box.remove(child);
-                    break;
-                }
-            }
+        let Some(widget) = child
+            .downcast_ref::<gtk::FlowBoxChild>()
+            .and_then(|flow_box_child| flow_box_child.get_child())
+        else {
+            continue;
+        };
+        let style_context = widget.get_style_context();
+        if widget.get_widget_name() != id {
+            style_context.remove_class("selected-section");
+            continue;
         }
+
+        style_context.add_class("selected-section");
+        scroll_to(&widget);
+    }
+}
+
+/// Scroll the scrolled window `widget` is in so `widget` is at the top
+fn scroll_to(widget: &gtk::Widget) {
+    let Some(scrolled_window) = widget
+        .get_ancestor(gtk::ScrolledWindow::static_type())
+        .and_then(|ancestor| ancestor.downcast::<gtk::ScrolledWindow>().ok())
+    else {
+        return;
+    };
+    // The content of the scrolled window is wrapped in a viewport
+    let Some(content) = scrolled_window
+        .get_child()
+        .and_then(|child| child.downcast::<gtk::Viewport>().ok())
+        .and_then(|viewport| viewport.get_child())
+    else {
+        return;
+    };
+    if let (Some((_, y)), Some(adjustment)) = (
+        widget.translate_coordinates(&content, 0, 0),
+        scrolled_window.get_vadjustment(),
+    ) {
+        adjustment.set_value((y as f64 - 10.0).max(0.0));
     }
 }
 
@@ -31,7 +190,7 @@ pub fn create_sphere_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox
         .load_from_path("src/gui/style.css")
         .expect("Failed to load CSS");
 
-    let sphere_count = app_state.borrow().spheres.len() + 1;
+    let sphere_count = app_state.borrow_mut().next_section_number("sphere");
     let unique_id = format!("sphere_{}", sphere_count); // Generate unique ID
 
     let grid = gtk::Grid::new();
@@ -41,10 +200,10 @@ pub fn create_sphere_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox
     println!("Grid set with widget name: {}", unique_id); // Debug print for grid ID
 
     let placeholders = vec!["0.0", "0.0", "0.0", "1.0"];
-    let sphere_entries = add_coordinate_widgets_grid(
+    let (name_entry, sphere_entries) = add_coordinate_widgets_grid(
         &grid,
         &provider,
-        &format!("Sphere {sphere_count}:"),
+        &format!("Sphere {sphere_count}"),
         &placeholders,
     );
     let pos_x_entry = &sphere_entries[0];
@@ -70,19 +229,29 @@ pub fn create_sphere_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox
     let color_button = gtk::ColorButton::new();
     grid.attach(&color_button, 0, 12, 1, 1); // Column 0, Row 12
 
+    let (strength_entry, temperature_entry) =
+        attach_emission_entries(&grid, &provider, &material_selector, 13);
+    let (pattern_scale_entry, second_color_button) =
+        attach_pattern_widgets(&grid, &provider, &material_selector, 17);
+
     let sphere_config = SphereConfig {
-        id: Rc::new(RefCell::new(sphere_count as u32)),
+        id: Rc::new(RefCell::new(sphere_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
         pos_x_entry: Rc::new(RefCell::new(pos_x_entry.clone())),
         pos_y_entry: Rc::new(RefCell::new(pos_y_entry.clone())),
         pos_z_entry: Rc::new(RefCell::new(pos_z_entry.clone())),
         radius_entry: Rc::new(RefCell::new(radius_entry.clone())),
         material_selector: Rc::new(RefCell::new(material_selector.clone())),
         color_button: Rc::new(RefCell::new(color_button.clone())),
+        strength_entry: Rc::new(RefCell::new(strength_entry)),
+        temperature_entry: Rc::new(RefCell::new(temperature_entry)),
+        pattern_scale_entry: Rc::new(RefCell::new(pattern_scale_entry)),
+        second_color_button: Rc::new(RefCell::new(second_color_button)),
     };
 
     // Create a randomize button for the sphere section
     let randomize_button = gtk::Button::with_label("Randomize");
-    grid.attach(&randomize_button, 0, 13, 1, 1); // Adjust the row number as needed
+    grid.attach(&randomize_button, 0, 21, 1, 1); // Adjust the row number as needed
 
     // Connect the randomize button click handler
     let pos_x_entry_clone = pos_x_entry.clone();
@@ -90,44 +259,35 @@ pub fn create_sphere_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox
     let pos_z_entry_clone = pos_z_entry.clone();
     let radius_entry_clone = radius_entry.clone();
     let material_selector_clone = material_selector.clone();
+    let history = app_state.borrow().history.clone();
     randomize_button.connect_clicked(move |_| {
-        let mut rng = rand::thread_rng();
-
-        pos_x_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        pos_y_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        pos_z_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        radius_entry_clone.set_text(&format!("{:.2}", rng.gen_range(0.1..1.0)));
-
-        // Randomly select a material
-        let random_material_index = rng.gen_range(0..MATERIALS.len());
-        material_selector_clone.set_active(Some(random_material_index as u32));
+        history.batch(|| {
+            let mut rng = rand::thread_rng();
+
+            pos_x_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            pos_y_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            pos_z_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            radius_entry_clone.set_text(&format!("{:.2}", rng.gen_range(0.1..1.0)));
+
+            // Randomly select a material
+            let random_material_index = rng.gen_range(0..MATERIALS.len());
+            material_selector_clone.set_active(Some(random_material_index as u32));
+        });
     });
 
     // Create a delete button for the sphere section
-    let delete_id = sphere_config.id.clone();
     let delete_button = Button::with_label("Delete");
-    println!("Adding delete button with ID: {}", *delete_id.borrow());
-    grid.attach(&delete_button, 0, 14, 1, 1); // Column 0, Row 13
-
-    // Connect the delete button click handler
-    delete_button.connect_clicked(clone!(@strong app_state, @strong flow_box => move |_| {
-    let id = format!("sphere_{}", *delete_id.borrow());
-
-    let mut app_state = app_state.borrow_mut();
-    if let Some(index) = app_state.spheres.iter().position(|s| format!("sphere_{}", *s.id.borrow()) == id) {
-        app_state.spheres.remove(index);
-    } else {
-        eprintln!("Error: No sphere with ID {} found in app_state", id);
-        return;
-    }
-    delete_component(&flow_box, id);
-    flow_box.show_all();
-}));
+    grid.attach(&delete_button, 0, 22, 1, 1); // Column 0, Row 22
 
-    app_state.borrow_mut().spheres.push(sphere_config);
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        sphere_config,
+    );
 
-    flow_box.add(&grid); // Directly add the grid to the flow_box
-    flow_box.show_all();
     grid.upcast::<gtk::Widget>() // Return the grid as a generic widget
 }
 
@@ -140,7 +300,7 @@ pub fn create_cylinder_section(
         .load_from_path("src/gui/style.css")
         .expect("Failed to load CSS");
 
-    let cylinder_count = app_state.borrow().cylinders.len() + 1;
+    let cylinder_count = app_state.borrow_mut().next_section_number("cylinder");
     let unique_id = format!("cylinder_{}", cylinder_count); // Generate unique ID
     println!("Creating cylinder section with ID: {}", unique_id); // Debug print for cylinder ID
 
@@ -148,10 +308,10 @@ pub fn create_cylinder_section(
     grid.set_column_spacing(5); // Adjust the spacing as needed
     grid.set_widget_name(&unique_id); // Set the ID of the grid
     let placeholders = vec!["0.0", "0.0", "0.0", "1.0", "2.0"];
-    let cylinder_entries = add_coordinate_widgets_grid(
+    let (name_entry, cylinder_entries) = add_coordinate_widgets_grid(
         &grid,
         &provider,
-        &format!("Cylinder {cylinder_count}:"),
+        &format!("Cylinder {cylinder_count}"),
         &placeholders,
     );
     let pos_x_entry = &cylinder_entries[0];
@@ -178,8 +338,14 @@ pub fn create_cylinder_section(
     let color_button = gtk::ColorButton::new();
     grid.attach(&color_button, 0, 14, 1, 1);
 
+    let (strength_entry, temperature_entry) =
+        attach_emission_entries(&grid, &provider, &material_selector, 15);
+    let (pattern_scale_entry, second_color_button) =
+        attach_pattern_widgets(&grid, &provider, &material_selector, 19);
+
     let cylinder_config = CylinderConfig {
-        id: Rc::new(RefCell::new(cylinder_count as u32)),
+        id: Rc::new(RefCell::new(cylinder_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
         pos_x_entry: Rc::new(RefCell::new(pos_x_entry.clone())),
         pos_y_entry: Rc::new(RefCell::new(pos_y_entry.clone())),
         pos_z_entry: Rc::new(RefCell::new(pos_z_entry.clone())),
@@ -187,10 +353,14 @@ pub fn create_cylinder_section(
         height_entry: Rc::new(RefCell::new(height_entry.clone())),
         material_selector: Rc::new(RefCell::new(material_selector.clone())),
         color_button: Rc::new(RefCell::new(color_button.clone())),
+        strength_entry: Rc::new(RefCell::new(strength_entry)),
+        temperature_entry: Rc::new(RefCell::new(temperature_entry)),
+        pattern_scale_entry: Rc::new(RefCell::new(pattern_scale_entry)),
+        second_color_button: Rc::new(RefCell::new(second_color_button)),
     };
 
     let randomize_button = gtk::Button::with_label("Randomize");
-    grid.attach(&randomize_button, 0, 15, 1, 1); // Adjust the row number as needed
+    grid.attach(&randomize_button, 0, 23, 1, 1); // Adjust the row number as needed
 
     // Connect the randomize button click handler
     let pos_x_entry_clone = pos_x_entry.clone();
@@ -199,46 +369,35 @@ pub fn create_cylinder_section(
     let radius_entry_clone = radius_entry.clone();
     let height_entry_clone = height_entry.clone();
     let material_selector_clone = material_selector.clone();
+    let history = app_state.borrow().history.clone();
     randomize_button.connect_clicked(move |_| {
-        let mut rng = rand::thread_rng();
-
-        pos_x_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        pos_y_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        pos_z_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        radius_entry_clone.set_text(&format!("{:.2}", rng.gen_range(0.1..5.0)));
-        height_entry_clone.set_text(&format!("{:.2}", rng.gen_range(0.1..5.0)));
-
-        // Randomly select a material
-        let random_material_index = rng.gen_range(0..MATERIALS.len());
-        material_selector_clone.set_active(Some(random_material_index as u32));
+        history.batch(|| {
+            let mut rng = rand::thread_rng();
+
+            pos_x_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            pos_y_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            pos_z_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            radius_entry_clone.set_text(&format!("{:.2}", rng.gen_range(0.1..5.0)));
+            height_entry_clone.set_text(&format!("{:.2}", rng.gen_range(0.1..5.0)));
+
+            // Randomly select a material
+            let random_material_index = rng.gen_range(0..MATERIALS.len());
+            material_selector_clone.set_active(Some(random_material_index as u32));
+        });
     });
 
     // Create a delete button for the cylinder section
-    let delete_id = cylinder_config.id.clone();
     let delete_button = gtk::Button::with_label("Delete");
-    grid.attach(&delete_button, 0, 16, 1, 1); // Column 0, Row 15
-
-    // Connect the delete button click handler
-    delete_button.connect_clicked(clone!(@strong app_state, @strong flow_box => move |_| {
-        let id = format!("cylinder_{}", *delete_id.borrow());
-
-        let mut app_state = app_state.borrow_mut();
-        if let Some(index) = app_state.cylinders.iter().position(|c| format!("cylinder_{}", *c.id.borrow()) == id) {
-            app_state.cylinders.remove(index);
-        } else {
-            eprintln!("Error: No cylinder with ID {} found in app_state", id);
-            return;
-        }
-        delete_component(&flow_box, id);
-        flow_box.show_all();
-    }));
+    grid.attach(&delete_button, 0, 24, 1, 1); // Column 0, Row 24
 
-    // Add the configuration to the AppState
-    app_state.borrow_mut().cylinders.push(cylinder_config);
-
-    flow_box.add(&grid);
-    flow_box.show_all();
-    print!("Added GUI element with ID: '{}'", unique_id); // Debug print for cylinder ID
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        cylinder_config,
+    );
     grid.upcast::<gtk::Widget>() // Return the grid as a generic widget
 }
 
@@ -251,7 +410,7 @@ pub fn create_cube_section(
         .load_from_path("src/gui/style.css")
         .expect("Failed to load CSS");
 
-    let cube_count = app_state.borrow().cubes.len() + 1;
+    let cube_count = app_state.borrow_mut().next_section_number("cube");
     let unique_id = format!("cube_{}", cube_count); // Generate unique ID
 
     let grid = gtk::Grid::new();
@@ -259,10 +418,10 @@ pub fn create_cube_section(
     grid.set_widget_name(&unique_id); // Set the ID of the grid
 
     let placeholders = vec!["0.0", "0.0", "0.0", "1.0"];
-    let cube_entries = add_coordinate_widgets_grid(
+    let (name_entry, cube_entries) = add_coordinate_widgets_grid(
         &grid,
         &provider,
-        &format!("Cube {cube_count}:"),
+        &format!("Cube {cube_count}"),
         &placeholders,
     );
     let pos_x_entry = &cube_entries[0];
@@ -288,20 +447,30 @@ pub fn create_cube_section(
     let color_button = gtk::ColorButton::new();
     grid.attach(&color_button, 0, 12, 1, 1); // Column 0, Row 12
 
+    let (strength_entry, temperature_entry) =
+        attach_emission_entries(&grid, &provider, &material_selector, 13);
+    let (pattern_scale_entry, second_color_button) =
+        attach_pattern_widgets(&grid, &provider, &material_selector, 17);
+
     grid.set_widget_name(&unique_id);
 
     let cube_config = CubeConfig {
-        id: Rc::new(RefCell::new(cube_count as u32)),
+        id: Rc::new(RefCell::new(cube_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
         pos_x_entry: Rc::new(RefCell::new(pos_x_entry.clone())),
         pos_y_entry: Rc::new(RefCell::new(pos_y_entry.clone())),
         pos_z_entry: Rc::new(RefCell::new(pos_z_entry.clone())),
         radius_entry: Rc::new(RefCell::new(radius_entry.clone())),
         material_selector: Rc::new(RefCell::new(material_selector.clone())),
         color_button: Rc::new(RefCell::new(color_button.clone())),
+        strength_entry: Rc::new(RefCell::new(strength_entry)),
+        temperature_entry: Rc::new(RefCell::new(temperature_entry)),
+        pattern_scale_entry: Rc::new(RefCell::new(pattern_scale_entry)),
+        second_color_button: Rc::new(RefCell::new(second_color_button)),
     };
 
     let randomize_button = gtk::Button::with_label("Randomize");
-    grid.attach(&randomize_button, 0, 13, 1, 1); // Adjust the row number as needed
+    grid.attach(&randomize_button, 0, 21, 1, 1); // Adjust the row number as needed
 
     // Connect the randomize button click handler
     let pos_x_entry_clone = pos_x_entry.clone();
@@ -309,41 +478,33 @@ pub fn create_cube_section(
     let pos_z_entry_clone = pos_z_entry.clone();
     let radius_entry_clone = radius_entry.clone();
     let material_selector_clone = material_selector.clone();
+    let history = app_state.borrow().history.clone();
     randomize_button.connect_clicked(move |_| {
-        let mut rng = rand::thread_rng();
-
-        pos_x_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        pos_y_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        pos_z_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
-        radius_entry_clone.set_text(&format!("{:.2}", rng.gen_range(0.1..5.0)));
-
-        // Randomly select a material
-        let random_material_index = rng.gen_range(0..MATERIALS.len());
-        material_selector_clone.set_active(Some(random_material_index as u32));
+        history.batch(|| {
+            let mut rng = rand::thread_rng();
+
+            pos_x_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            pos_y_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            pos_z_entry_clone.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            radius_entry_clone.set_text(&format!("{:.2}", rng.gen_range(0.1..5.0)));
+
+            // Randomly select a material
+            let random_material_index = rng.gen_range(0..MATERIALS.len());
+            material_selector_clone.set_active(Some(random_material_index as u32));
+        });
     });
-    let delete_id = cube_config.id.clone();
     let delete_button = gtk::Button::with_label("Delete");
-    grid.attach(&delete_button, 0, 14, 1, 1); //Column 0, Row 13
-
-    // Connect a handler to the delete button
-    delete_button.connect_clicked(clone!(@strong app_state, @strong flow_box => move |_| {
-        let id = format!("cube_{}", *delete_id.borrow());
-        let mut app_state = app_state.borrow_mut();
-        if let Some(index) = app_state.cubes.iter().position(|c| format!("cube_{}", *c.id.borrow()) == id) {
-            app_state.cubes.remove(index);
-        } else {
-            eprintln!("Error: No cube with ID {} found in app_state", id);
-            return;
-        }
-        delete_component(&flow_box, id);
-        flow_box.show_all();
-    }));
+    grid.attach(&delete_button, 0, 22, 1, 1); // Column 0, Row 22
 
-    app_state.borrow_mut().cubes.push(cube_config);
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        cube_config,
+    );
 
-    flow_box.add(&grid); // Directly add the grid to the flow_box
-    flow_box.show_all();
-    print!("Added GUI element with ID: '{}'", unique_id); // Debug print for cube ID
     grid.upcast::<gtk::Widget>() // Return the grid as a generic widget
 }
 
@@ -356,7 +517,7 @@ pub fn create_flat_plane_section(
         .load_from_path("src/gui/style.css")
         .expect("Failed to load CSS");
 
-    let flat_plane_count = app_state.borrow().flat_planes.len() + 1;
+    let flat_plane_count = app_state.borrow_mut().next_section_number("flat_plane");
     let unique_id = format!("flat_plane_{}", flat_plane_count); // Generate unique ID
 
     let grid = gtk::Grid::new();
@@ -364,10 +525,10 @@ pub fn create_flat_plane_section(
     grid.set_widget_name(&unique_id); // Set the ID of the grid
 
     let placeholders = vec!["0.0", "0.0", "0.0", "10.0"];
-    let flat_plane_entries = add_coordinate_widgets_grid(
+    let (name_entry, flat_plane_entries) = add_coordinate_widgets_grid(
         &grid,
         &provider,
-        &format!("Flat Plane {flat_plane_count}:"),
+        &format!("Flat Plane {flat_plane_count}"),
         &placeholders,
     );
     let pos_x_entry = &flat_plane_entries[0];
@@ -393,44 +554,423 @@ pub fn create_flat_plane_section(
     let color_button = gtk::ColorButton::new();
     grid.attach(&color_button, 0, 12, 1, 1);
 
+    let (strength_entry, temperature_entry) =
+        attach_emission_entries(&grid, &provider, &material_selector, 13);
+    let (pattern_scale_entry, second_color_button) =
+        attach_pattern_widgets(&grid, &provider, &material_selector, 17);
+
     grid.set_widget_name(&unique_id);
 
     let flat_plane_config = FlatPlaneConfig {
-        id: Rc::new(RefCell::new(flat_plane_count as u32)),
+        id: Rc::new(RefCell::new(flat_plane_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
         pos_x_entry: Rc::new(RefCell::new(pos_x_entry.clone())),
         pos_y_entry: Rc::new(RefCell::new(pos_y_entry.clone())),
         pos_z_entry: Rc::new(RefCell::new(pos_z_entry.clone())),
         radius_entry: Rc::new(RefCell::new(radius_entry.clone())),
         material_selector: Rc::new(RefCell::new(material_selector)),
         color_button: Rc::new(RefCell::new(color_button)),
+        strength_entry: Rc::new(RefCell::new(strength_entry)),
+        temperature_entry: Rc::new(RefCell::new(temperature_entry)),
+        pattern_scale_entry: Rc::new(RefCell::new(pattern_scale_entry)),
+        second_color_button: Rc::new(RefCell::new(second_color_button)),
     };
 
-    let delete_id = flat_plane_config.id.clone();
     let delete_button = gtk::Button::with_label("Delete");
-    grid.attach(&delete_button, 0, 13, 1, 1); //Column 0, Row 13
-
-    // Connect a handler to the delete button
-    delete_button.connect_clicked(clone!(@strong app_state, @strong flow_box => move |_| {
-        let id_number = *delete_id.borrow();
-        let id = format!("flat_plane_{}", id_number);
-
-        let mut app_state = app_state.borrow_mut();
-        if let Some(index) = app_state.flat_planes.iter().position(|fp| format!("flat_plane_{}", *fp.id.borrow()) == id) {
-            app_state.flat_planes.remove(index);
-        } else {
-            eprintln!("Error: No flat_plane with ID {} found in app_state", id);
-            return;
-        }
+    grid.attach(&delete_button, 0, 21, 1, 1); // Column 0, Row 21
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        flat_plane_config,
+    );
+    grid.upcast::<gtk::Widget>() // Return the grid as a generic widget
+}
 
-        delete_component(&flow_box, id);
+pub fn create_cone_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+    let provider = CssProvider::new();
+    provider
+        .load_from_path("src/gui/style.css")
+        .expect("Failed to load CSS");
 
-        flow_box.show_all();
-    }));
+    let cone_count = app_state.borrow_mut().next_section_number("cone");
+    let unique_id = format!("cone_{}", cone_count); // Generate unique ID
 
-    app_state.borrow_mut().flat_planes.push(flat_plane_config);
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(5);
+    grid.set_widget_name(&unique_id);
 
-    flow_box.add(&grid); // Directly add the grid to the flow_box
-    flow_box.show_all();
+    let (name_entry, entries, row) = add_labeled_entries_grid(
+        &grid,
+        &provider,
+        &format!("Cone {cone_count}"),
+        &[
+            ("X pos", "0.0"),
+            ("Y pos", "0.0"),
+            ("Z pos", "0.0"),
+            ("Radius", "1.0"),
+            ("Top radius", "0.0"),
+            ("Height", "2.0"),
+        ],
+    );
+    let material = attach_material_widgets(&grid, &provider, row);
+
+    let cone_config = ConeConfig {
+        id: Rc::new(RefCell::new(cone_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
+        pos_x_entry: Rc::new(RefCell::new(entries[0].clone())),
+        pos_y_entry: Rc::new(RefCell::new(entries[1].clone())),
+        pos_z_entry: Rc::new(RefCell::new(entries[2].clone())),
+        radius_entry: Rc::new(RefCell::new(entries[3].clone())),
+        top_radius_entry: Rc::new(RefCell::new(entries[4].clone())),
+        height_entry: Rc::new(RefCell::new(entries[5].clone())),
+        material_selector: Rc::new(RefCell::new(material.material_selector.clone())),
+        color_button: Rc::new(RefCell::new(material.color_button)),
+        strength_entry: Rc::new(RefCell::new(material.strength_entry)),
+        temperature_entry: Rc::new(RefCell::new(material.temperature_entry)),
+        pattern_scale_entry: Rc::new(RefCell::new(material.pattern_scale_entry)),
+        second_color_button: Rc::new(RefCell::new(material.second_color_button)),
+    };
 
-    grid.upcast::<gtk::Widget>() // Return the grid as a generic widget
+    let randomize_button = gtk::Button::with_label("Randomize");
+    grid.attach(&randomize_button, 0, row + 12, 1, 1);
+
+    // Connect the randomize button click handler
+    let material_selector = material.material_selector;
+    let history = app_state.borrow().history.clone();
+    randomize_button.connect_clicked(move |_| {
+        history.batch(|| {
+            let mut rng = rand::thread_rng();
+
+            for entry in &entries[..3] {
+                entry.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            }
+            entries[3].set_text(&format!("{:.2}", rng.gen_range(0.1..3.0)));
+            entries[4].set_text(&format!("{:.2}", rng.gen_range(0.0..1.0)));
+            entries[5].set_text(&format!("{:.2}", rng.gen_range(0.1..5.0)));
+
+            // Randomly select a material
+            let random_material_index = rng.gen_range(0..MATERIALS.len());
+            material_selector.set_active(Some(random_material_index as u32));
+        });
+    });
+
+    let delete_button = gtk::Button::with_label("Delete");
+    grid.attach(&delete_button, 0, row + 13, 1, 1);
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        cone_config,
+    );
+
+    grid.upcast::<gtk::Widget>()
+}
+
+pub fn create_torus_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+    let provider = CssProvider::new();
+    provider
+        .load_from_path("src/gui/style.css")
+        .expect("Failed to load CSS");
+
+    let torus_count = app_state.borrow_mut().next_section_number("torus");
+    let unique_id = format!("torus_{}", torus_count); // Generate unique ID
+
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(5);
+    grid.set_widget_name(&unique_id);
+
+    let (name_entry, entries, row) = add_labeled_entries_grid(
+        &grid,
+        &provider,
+        &format!("Torus {torus_count}"),
+        &[
+            ("X pos", "0.0"),
+            ("Y pos", "0.0"),
+            ("Z pos", "0.0"),
+            ("Major radius", "2.0"),
+            ("Minor radius", "0.5"),
+        ],
+    );
+    let material = attach_material_widgets(&grid, &provider, row);
+
+    let torus_config = TorusConfig {
+        id: Rc::new(RefCell::new(torus_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
+        pos_x_entry: Rc::new(RefCell::new(entries[0].clone())),
+        pos_y_entry: Rc::new(RefCell::new(entries[1].clone())),
+        pos_z_entry: Rc::new(RefCell::new(entries[2].clone())),
+        major_radius_entry: Rc::new(RefCell::new(entries[3].clone())),
+        minor_radius_entry: Rc::new(RefCell::new(entries[4].clone())),
+        material_selector: Rc::new(RefCell::new(material.material_selector.clone())),
+        color_button: Rc::new(RefCell::new(material.color_button)),
+        strength_entry: Rc::new(RefCell::new(material.strength_entry)),
+        temperature_entry: Rc::new(RefCell::new(material.temperature_entry)),
+        pattern_scale_entry: Rc::new(RefCell::new(material.pattern_scale_entry)),
+        second_color_button: Rc::new(RefCell::new(material.second_color_button)),
+    };
+
+    let randomize_button = gtk::Button::with_label("Randomize");
+    grid.attach(&randomize_button, 0, row + 12, 1, 1);
+
+    // Connect the randomize button click handler
+    let material_selector = material.material_selector;
+    let history = app_state.borrow().history.clone();
+    randomize_button.connect_clicked(move |_| {
+        history.batch(|| {
+            let mut rng = rand::thread_rng();
+
+            for entry in &entries[..3] {
+                entry.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            }
+            entries[3].set_text(&format!("{:.2}", rng.gen_range(1.0..4.0)));
+            entries[4].set_text(&format!("{:.2}", rng.gen_range(0.1..1.0)));
+
+            // Randomly select a material
+            let random_material_index = rng.gen_range(0..MATERIALS.len());
+            material_selector.set_active(Some(random_material_index as u32));
+        });
+    });
+
+    let delete_button = gtk::Button::with_label("Delete");
+    grid.attach(&delete_button, 0, row + 13, 1, 1);
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        torus_config,
+    );
+
+    grid.upcast::<gtk::Widget>()
+}
+
+pub fn create_capsule_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+    let provider = CssProvider::new();
+    provider
+        .load_from_path("src/gui/style.css")
+        .expect("Failed to load CSS");
+
+    let capsule_count = app_state.borrow_mut().next_section_number("capsule");
+    let unique_id = format!("capsule_{}", capsule_count); // Generate unique ID
+
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(5);
+    grid.set_widget_name(&unique_id);
+
+    let (name_entry, entries, row) = add_labeled_entries_grid(
+        &grid,
+        &provider,
+        &format!("Capsule {capsule_count}"),
+        &[
+            ("X pos", "0.0"),
+            ("Y pos", "0.0"),
+            ("Z pos", "0.0"),
+            ("Radius", "0.5"),
+            ("Height", "2.0"),
+        ],
+    );
+    let material = attach_material_widgets(&grid, &provider, row);
+
+    let capsule_config = CapsuleConfig {
+        id: Rc::new(RefCell::new(capsule_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
+        pos_x_entry: Rc::new(RefCell::new(entries[0].clone())),
+        pos_y_entry: Rc::new(RefCell::new(entries[1].clone())),
+        pos_z_entry: Rc::new(RefCell::new(entries[2].clone())),
+        radius_entry: Rc::new(RefCell::new(entries[3].clone())),
+        height_entry: Rc::new(RefCell::new(entries[4].clone())),
+        material_selector: Rc::new(RefCell::new(material.material_selector.clone())),
+        color_button: Rc::new(RefCell::new(material.color_button)),
+        strength_entry: Rc::new(RefCell::new(material.strength_entry)),
+        temperature_entry: Rc::new(RefCell::new(material.temperature_entry)),
+        pattern_scale_entry: Rc::new(RefCell::new(material.pattern_scale_entry)),
+        second_color_button: Rc::new(RefCell::new(material.second_color_button)),
+    };
+
+    let randomize_button = gtk::Button::with_label("Randomize");
+    grid.attach(&randomize_button, 0, row + 12, 1, 1);
+
+    // Connect the randomize button click handler
+    let material_selector = material.material_selector;
+    let history = app_state.borrow().history.clone();
+    randomize_button.connect_clicked(move |_| {
+        history.batch(|| {
+            let mut rng = rand::thread_rng();
+
+            for entry in &entries[..3] {
+                entry.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            }
+            entries[3].set_text(&format!("{:.2}", rng.gen_range(0.1..2.0)));
+            entries[4].set_text(&format!("{:.2}", rng.gen_range(0.1..5.0)));
+
+            // Randomly select a material
+            let random_material_index = rng.gen_range(0..MATERIALS.len());
+            material_selector.set_active(Some(random_material_index as u32));
+        });
+    });
+
+    let delete_button = gtk::Button::with_label("Delete");
+    grid.attach(&delete_button, 0, row + 13, 1, 1);
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        capsule_config,
+    );
+
+    grid.upcast::<gtk::Widget>()
+}
+
+pub fn create_ellipsoid_section(
+    app_state: Rc<RefCell<AppState>>,
+    flow_box: FlowBox,
+) -> gtk::Widget {
+    let provider = CssProvider::new();
+    provider
+        .load_from_path("src/gui/style.css")
+        .expect("Failed to load CSS");
+
+    let ellipsoid_count = app_state.borrow_mut().next_section_number("ellipsoid");
+    let unique_id = format!("ellipsoid_{}", ellipsoid_count); // Generate unique ID
+
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(5);
+    grid.set_widget_name(&unique_id);
+
+    let (name_entry, entries, row) = add_labeled_entries_grid(
+        &grid,
+        &provider,
+        &format!("Ellipsoid {ellipsoid_count}"),
+        &[
+            ("X pos", "0.0"),
+            ("Y pos", "0.0"),
+            ("Z pos", "0.0"),
+            ("X radius", "2.0"),
+            ("Y radius", "1.0"),
+            ("Z radius", "1.0"),
+        ],
+    );
+    let material = attach_material_widgets(&grid, &provider, row);
+
+    let ellipsoid_config = EllipsoidConfig {
+        id: Rc::new(RefCell::new(ellipsoid_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
+        pos_x_entry: Rc::new(RefCell::new(entries[0].clone())),
+        pos_y_entry: Rc::new(RefCell::new(entries[1].clone())),
+        pos_z_entry: Rc::new(RefCell::new(entries[2].clone())),
+        radius_x_entry: Rc::new(RefCell::new(entries[3].clone())),
+        radius_y_entry: Rc::new(RefCell::new(entries[4].clone())),
+        radius_z_entry: Rc::new(RefCell::new(entries[5].clone())),
+        material_selector: Rc::new(RefCell::new(material.material_selector.clone())),
+        color_button: Rc::new(RefCell::new(material.color_button)),
+        strength_entry: Rc::new(RefCell::new(material.strength_entry)),
+        temperature_entry: Rc::new(RefCell::new(material.temperature_entry)),
+        pattern_scale_entry: Rc::new(RefCell::new(material.pattern_scale_entry)),
+        second_color_button: Rc::new(RefCell::new(material.second_color_button)),
+    };
+
+    let randomize_button = gtk::Button::with_label("Randomize");
+    grid.attach(&randomize_button, 0, row + 12, 1, 1);
+
+    // Connect the randomize button click handler
+    let material_selector = material.material_selector;
+    let history = app_state.borrow().history.clone();
+    randomize_button.connect_clicked(move |_| {
+        history.batch(|| {
+            let mut rng = rand::thread_rng();
+
+            for entry in &entries[..3] {
+                entry.set_text(&format!("{:.2}", rng.gen_range(-10.0..10.0)));
+            }
+            entries[3].set_text(&format!("{:.2}", rng.gen_range(0.1..3.0)));
+            entries[4].set_text(&format!("{:.2}", rng.gen_range(0.1..3.0)));
+            entries[5].set_text(&format!("{:.2}", rng.gen_range(0.1..3.0)));
+
+            // Randomly select a material
+            let random_material_index = rng.gen_range(0..MATERIALS.len());
+            material_selector.set_active(Some(random_material_index as u32));
+        });
+    });
+
+    let delete_button = gtk::Button::with_label("Delete");
+    grid.attach(&delete_button, 0, row + 13, 1, 1);
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        ellipsoid_config,
+    );
+    grid.upcast::<gtk::Widget>()
+}
+
+/// A group moves the objects and groups listed in "Members" together. Members are the names of
+/// their sections, e.g. "Cube 1, Cylinder 2".
+pub fn create_group_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+    let provider = CssProvider::new();
+    provider
+        .load_from_path("src/gui/style.css")
+        .expect("Failed to load CSS");
+
+    let group_count = app_state.borrow_mut().next_section_number("group");
+    let unique_id = format!("group_{}", group_count); // Generate unique ID
+
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(5);
+    grid.set_widget_name(&unique_id);
+
+    let (name_entry, entries, row) = add_labeled_entries_grid(
+        &grid,
+        &provider,
+        &format!("Group {group_count}"),
+        &[
+            ("X move", "0.0"),
+            ("Y move", "0.0"),
+            ("Z move", "0.0"),
+            ("X rotation", "0.0"),
+            ("Y rotation", "0.0"),
+            ("Z rotation", "0.0"),
+            ("Members", ""),
+        ],
+    );
+
+    let group_config = GroupConfig {
+        id: Rc::new(RefCell::new(group_count)),
+        name_entry: Rc::new(RefCell::new(name_entry)),
+        pos_x_entry: Rc::new(RefCell::new(entries[0].clone())),
+        pos_y_entry: Rc::new(RefCell::new(entries[1].clone())),
+        pos_z_entry: Rc::new(RefCell::new(entries[2].clone())),
+        rot_x_entry: Rc::new(RefCell::new(entries[3].clone())),
+        rot_y_entry: Rc::new(RefCell::new(entries[4].clone())),
+        rot_z_entry: Rc::new(RefCell::new(entries[5].clone())),
+        members_entry: Rc::new(RefCell::new(entries[6].clone())),
+    };
+
+    let delete_button = gtk::Button::with_label("Delete");
+    grid.attach(&delete_button, 0, row, 1, 1);
+
+    add_section(
+        &app_state,
+        &flow_box,
+        &grid,
+        &provider,
+        &delete_button,
+        group_config,
+    );
+
+    grid.upcast::<gtk::Widget>()
 }
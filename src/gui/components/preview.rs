@@ -0,0 +1,457 @@
+use crate::gui::components::camera_pixbuf;
+use crate::gui::*;
+use crate::raytracer::{Camera, CameraBuilder, Scene, Viewpoint};
+use gdk::prelude::GdkContextExt;
+use gtk::CheckButton;
+use rayon::prelude::*;
+
+/// Width of the preview in pixels, the height follows the aspect ratio of the resolution
+const PREVIEW_WIDTH: u32 = 320;
+/// Samples per pixel after which the preview stops refining
+const PREVIEW_SAMPLES: u16 = 32;
+/// Milliseconds between two preview passes
+const PREVIEW_INTERVAL: u32 = 30;
+/// Passes the camera has to stay still before the selected object is outlined again
+const OUTLINE_DELAY: u16 = 4;
+/// Degrees of orbit per pixel dragged
+const ORBIT_SPEED: f64 = 0.4;
+/// Fraction of the distance to the look at point panned per pixel dragged
+const PAN_SPEED: f64 = 0.003;
+/// Fraction of the distance to the look at point flown per key press
+const FLY_STEP: f64 = 0.05;
+const ZOOM_STEP: f64 = 0.9;
+
+/// The camera entries of the main window, which the preview reads and updates
+#[derive(Clone)]
+pub struct CameraEntries {
+    pub position: [Entry; 3],
+    pub look_at: [Entry; 3],
+    pub width: Entry,
+    pub height: Entry,
+}
+
+impl CameraEntries {
+    pub fn viewpoint(&self) -> Option<Viewpoint> {
+        let read = |entries: &[Entry; 3]| -> Option<Vector3<f64>> {
+            let [x, y, z] = entries
+                .each_ref()
+                .map(|entry| entry.get_text().parse::<f64>());
+            Some(Vector3::new(x.ok()?, y.ok()?, z.ok()?))
+        };
+        Some(Viewpoint::new(read(&self.position)?, read(&self.look_at)?))
+    }
+
+    pub fn set_viewpoint(&self, viewpoint: &Viewpoint) {
+        for (entries, point) in [
+            (&self.position, viewpoint.position),
+            (&self.look_at, viewpoint.look_at),
+        ] {
+            for (entry, value) in entries.iter().zip(point.iter()) {
+                entry.set_text(&format!("{value:.2}"));
+            }
+        }
+    }
+
+    /// Width divided by height of the resolution entries, 4:3 if they are not valid
+    pub fn aspect_ratio(&self) -> f64 {
+        match (
+            self.width.get_text().parse::<u32>(),
+            self.height.get_text().parse::<u32>(),
+        ) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => width as f64 / height as f64,
+            _ => 4.0 / 3.0,
+        }
+    }
+}
+
+/// Called with the section ID of the object clicked on in the preview
+type SelectCallback = Box<dyn Fn(&str)>;
+
+#[derive(Default)]
+struct PreviewState {
+    camera: Option<Camera>,
+    scene: Option<Arc<Scene>>,
+    pixbuf: Option<Pixbuf>,
+    sections: SectionObjects,
+    /// The mouse button held down and where the mouse was last
+    drag: Option<(u32, (f64, f64))>,
+    /// Whether the mouse moved while the button was held down, so it was not a click
+    dragged: bool,
+    /// Section ID of the object clicked on, and the pixels outlining it
+    selected: Option<String>,
+    outline: Vec<(u32, u32)>,
+    /// Whether the camera or the scene changed since the outline was traced
+    outline_pending: bool,
+    running: bool,
+}
+
+/// `Preview` is a small viewport that renders the scene progressively, one sample per pixel at
+/// a time, while "Live preview" is on.
+///
+/// Dragging with the left mouse button orbits around the look at point, the middle or right
+/// button pans, and scrolling zooms. W, A, S and D fly forward, left, back and right, Q and E
+/// down and up. Every move updates the camera entries, which starts the preview over, and every
+/// edit recorded in the history rebuilds its scene.
+///
+/// Clicking on an object outlines it and calls the callback given to `connect_selected` with the
+/// ID of its section.
+pub struct Preview {
+    pub drawing_area: gtk::DrawingArea,
+    pub toggle: CheckButton,
+    app_state: Rc<RefCell<AppState>>,
+    entries: CameraEntries,
+    field_of_view_scale: Scale,
+    state: RefCell<PreviewState>,
+    on_select: RefCell<Option<SelectCallback>>,
+}
+
+impl Preview {
+    pub fn new(
+        parent: &GtkBox,
+        app_state: Rc<RefCell<AppState>>,
+        entries: CameraEntries,
+        field_of_view_scale: Scale,
+    ) -> Rc<Self> {
+        let toggle = CheckButton::with_label("Live preview");
+        let drawing_area = gtk::DrawingArea::new();
+        drawing_area.set_can_focus(true);
+        drawing_area.add_events(
+            gdk::EventMask::BUTTON_PRESS_MASK
+                | gdk::EventMask::BUTTON_RELEASE_MASK
+                | gdk::EventMask::BUTTON_MOTION_MASK
+                | gdk::EventMask::SCROLL_MASK
+                | gdk::EventMask::KEY_PRESS_MASK,
+        );
+        drawing_area.set_no_show_all(true);
+
+        let preview_box = GtkBox::new(Orientation::Vertical, 5);
+        preview_box.set_halign(gtk::Align::Center);
+        preview_box.pack_start(&toggle, false, false, 0);
+        preview_box.pack_start(&drawing_area, false, false, 0);
+        parent.pack_start(&preview_box, false, false, 0);
+
+        let preview = Rc::new(Self {
+            drawing_area,
+            toggle,
+            app_state,
+            entries,
+            field_of_view_scale,
+            state: RefCell::new(PreviewState::default()),
+            on_select: RefCell::new(None),
+        });
+        preview.connect_signals();
+        preview
+    }
+
+    fn connect_signals(self: &Rc<Self>) {
+        // The toggle keeps the preview alive for as long as the window is open
+        self.toggle
+            .connect_toggled(clone!(@strong self as preview => move |toggle| {
+                if toggle.get_active() {
+                    preview.drawing_area.show();
+                    preview.refresh_scene();
+                    preview.start();
+                } else {
+                    preview.drawing_area.hide();
+                }
+            }));
+
+        // Sections added, deleted, edited, undone or redone change the scene
+        let history = self.app_state.borrow().history.clone();
+        history.connect_changed(clone!(@weak self as preview => move || {
+            if preview.toggle.get_active() {
+                preview.refresh_scene();
+            }
+        }));
+
+        let camera_entries = self.entries.position.iter().chain(&self.entries.look_at);
+        for entry in camera_entries.chain([&self.entries.width, &self.entries.height]) {
+            entry.connect_changed(clone!(@weak self as preview => move |_| {
+                if preview.toggle.get_active() {
+                    preview.restart();
+                }
+            }));
+        }
+        self.field_of_view_scale
+            .connect_value_changed(clone!(@weak self as preview => move |_| {
+                if preview.toggle.get_active() {
+                    preview.restart();
+                }
+            }));
+
+        self.drawing_area.connect_draw(
+            clone!(@weak self as preview => @default-return Inhibit(false), move |_, cr| {
+                let state = preview.state.borrow();
+                if let Some(pixbuf) = &state.pixbuf {
+                    cr.set_source_pixbuf(pixbuf, 0.0, 0.0);
+                    cr.paint();
+                }
+
+                cr.set_source_rgba(1.0, 0.65, 0.0, 1.0);
+                for &(x, y) in &state.outline {
+                    cr.rectangle(x as f64, y as f64, 1.0, 1.0);
+                }
+                cr.fill();
+                Inhibit(false)
+            }),
+        );
+
+        self.drawing_area.connect_button_press_event(
+            clone!(@weak self as preview => @default-return Inhibit(false), move |area, event| {
+                area.grab_focus();
+                let mut state = preview.state.borrow_mut();
+                state.drag = Some((event.get_button(), event.get_position()));
+                state.dragged = false;
+                Inhibit(true)
+            }),
+        );
+
+        self.drawing_area.connect_button_release_event(
+            clone!(@weak self as preview => @default-return Inhibit(false), move |_, event| {
+                let drag = preview.state.borrow_mut().drag.take();
+                let dragged = preview.state.borrow().dragged;
+                if matches!(drag, Some((1, _))) && !dragged {
+                    let (x, y) = event.get_position();
+                    preview.select_at(x, y);
+                }
+                Inhibit(true)
+            }),
+        );
+
+        self.drawing_area
+            .connect_motion_notify_event(clone!(@weak self as preview => @default-return Inhibit(false), move |_, event| {
+                let (x, y) = event.get_position();
+                let Some((button, (last_x, last_y))) = preview.state.borrow().drag else {
+                    return Inhibit(false);
+                };
+                let mut state = preview.state.borrow_mut();
+                state.drag = Some((button, (x, y)));
+                state.dragged = true;
+                drop(state);
+
+                let (dx, dy) = (x - last_x, y - last_y);
+                // The scene follows the mouse, so the camera moves the other way
+                if button == 1 {
+                    preview.navigate(|viewpoint| viewpoint.orbit(-dx * ORBIT_SPEED, dy * ORBIT_SPEED));
+                } else {
+                    preview.navigate(|viewpoint| viewpoint.pan(-dx * PAN_SPEED, dy * PAN_SPEED));
+                }
+                Inhibit(true)
+            }));
+
+        self.drawing_area
+            .connect_scroll_event(clone!(@weak self as preview => @default-return Inhibit(false), move |_, event| {
+                match event.get_direction() {
+                    gdk::ScrollDirection::Up => preview.navigate(|viewpoint| viewpoint.zoom(ZOOM_STEP)),
+                    gdk::ScrollDirection::Down => {
+                        preview.navigate(|viewpoint| viewpoint.zoom(1.0 / ZOOM_STEP))
+                    }
+                    _ => {}
+                }
+                Inhibit(true)
+            }));
+
+        self.drawing_area.connect_key_press_event(
+            clone!(@weak self as preview => @default-return Inhibit(false), move |_, event| {
+                let Some(key) = event.get_keyval().to_unicode() else {
+                    return Inhibit(false);
+                };
+                let (forward, right, up) = match key.to_ascii_lowercase() {
+                    'w' => (1.0, 0.0, 0.0),
+                    's' => (-1.0, 0.0, 0.0),
+                    'a' => (0.0, -1.0, 0.0),
+                    'd' => (0.0, 1.0, 0.0),
+                    'e' => (0.0, 0.0, 1.0),
+                    'q' => (0.0, 0.0, -1.0),
+                    _ => return Inhibit(false),
+                };
+                preview.navigate(|viewpoint| {
+                    let step = viewpoint.distance() * FLY_STEP;
+                    viewpoint.fly(forward * step, right * step, up * step)
+                });
+                Inhibit(true)
+            }),
+        );
+    }
+
+    /// Move the camera and write the new viewpoint to the entries, which start the preview over
+    fn navigate(&self, change: impl FnOnce(&Viewpoint) -> Viewpoint) {
+        // Nothing to move while the entries are being edited into a valid position
+        let Some(viewpoint) = self.entries.viewpoint() else {
+            return;
+        };
+        // Recorded as one edit, so a whole drag is undone at once
+        let history = self.app_state.borrow().history.clone();
+        history.batch(|| self.entries.set_viewpoint(&change(&viewpoint)));
+    }
+
+    /// Call `callback` with the section ID of every object clicked on
+    pub fn connect_selected(&self, callback: impl Fn(&str) + 'static) {
+        *self.on_select.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Build the scene from the GUI again, e.g. after objects were added
+    pub fn refresh_scene(&self) {
+        self.rebuild_scene();
+        self.restart();
+    }
+
+    /// The scene and the objects of every section as they are in the GUI right now
+    fn rebuild_scene(&self) {
+        let (scene, sections) = scene_with_sections_from_gui(self.app_state.clone());
+        let mut state = self.state.borrow_mut();
+        state.scene = Some(Arc::new(scene));
+        state.sections = sections;
+    }
+
+    /// Select the object under the pixel at `x`, `y`, or nothing if there is none
+    fn select_at(&self, x: f64, y: f64) {
+        // Picked from the sections as they are now, not as they were when the preview started
+        self.rebuild_scene();
+        let mut state = self.state.borrow_mut();
+        let hit_object = match (&state.camera, &state.scene) {
+            (Some(camera), Some(scene)) => camera
+                .pixel_ray(x.max(0.0) as u32, y.max(0.0) as u32)
+                .and_then(|ray| scene.hit_object(&ray)),
+            _ => None,
+        };
+        state.selected = hit_object.and_then(|hit_object| {
+            state
+                .sections
+                .iter()
+                .find(|(_, object)| Arc::ptr_eq(object, &hit_object))
+                .map(|(id, _)| id.clone())
+        });
+        let selected = state.selected.clone();
+        drop(state);
+
+        self.update_outline();
+        if let (Some(id), Some(on_select)) = (selected, &*self.on_select.borrow()) {
+            on_select(&id);
+        }
+    }
+
+    /// Find the pixels on the edge of the selected object, as seen by the preview camera
+    fn update_outline(&self) {
+        let mut state = self.state.borrow_mut();
+        state.outline_pending = false;
+        let selected_object = state.selected.as_ref().and_then(|selected| {
+            state
+                .sections
+                .iter()
+                .find(|(id, _)| id == selected)
+                .map(|(_, object)| object.clone())
+        });
+        let (Some(selected_object), Some(camera), Some(scene)) =
+            (selected_object, &state.camera, &state.scene)
+        else {
+            state.outline.clear();
+            self.drawing_area.queue_draw();
+            return;
+        };
+
+        let (width, height) = camera.resolution;
+        let covered: Vec<bool> = (0..width * height)
+            .into_par_iter()
+            .map(|pixel| {
+                camera
+                    .pixel_ray(pixel % width, pixel / width)
+                    .and_then(|ray| scene.hit_object(&ray))
+                    .is_some_and(|object| Arc::ptr_eq(&object, &selected_object))
+            })
+            .collect();
+        let is_covered = |x: i64, y: i64| {
+            (0..width as i64).contains(&x)
+                && (0..height as i64).contains(&y)
+                && covered[(y * width as i64 + x) as usize]
+        };
+
+        state.outline = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                let (x, y) = (x as i64, y as i64);
+                is_covered(x, y)
+                    && !(is_covered(x - 1, y)
+                        && is_covered(x + 1, y)
+                        && is_covered(x, y - 1)
+                        && is_covered(x, y + 1))
+            })
+            .collect();
+        drop(state);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Throw away the samples so far and render from the current camera entries
+    pub fn restart(&self) {
+        let Some(viewpoint) = self.entries.viewpoint() else {
+            return;
+        };
+        let height = (PREVIEW_WIDTH as f64 / self.entries.aspect_ratio()).round() as u32;
+        self.drawing_area
+            .set_size_request(PREVIEW_WIDTH as i32, height.max(1) as i32);
+
+        let camera = CameraBuilder::new()
+            .sample_size(0)
+            .position_by_coordinates(viewpoint.position)
+            .look_at(viewpoint.look_at)
+            .vertical_field_of_view(self.field_of_view_scale.get_value())
+            .resolution(PREVIEW_WIDTH, height.max(1))
+            .build();
+        let mut state = self.state.borrow_mut();
+        state.camera = Some(camera);
+        // Traced by `render_pass` later, so a drag does not trace it for every step
+        state.outline.clear();
+        state.outline_pending = state.selected.is_some();
+    }
+
+    /// Keep rendering passes until the preview is turned off or has all its samples
+    fn start(self: &Rc<Self>) {
+        if std::mem::replace(&mut self.state.borrow_mut().running, true) {
+            return;
+        }
+
+        glib::timeout_add_local(
+            PREVIEW_INTERVAL,
+            clone!(@weak self as preview => @default-return glib::Continue(false), move || {
+                let keep_running = preview.toggle.get_active();
+                if keep_running {
+                    preview.render_pass();
+                } else {
+                    preview.state.borrow_mut().running = false;
+                }
+                glib::Continue(keep_running)
+            }),
+        );
+    }
+
+    /// Add one sample to every pixel of the preview, and outline the selected object again once
+    /// the camera stopped moving
+    fn render_pass(&self) {
+        let outline_due = {
+            let state = self.state.borrow();
+            state.outline_pending
+                && state
+                    .camera
+                    .as_ref()
+                    .is_some_and(|camera| camera.sample_size >= OUTLINE_DELAY)
+        };
+        if outline_due {
+            self.update_outline();
+        }
+
+        let mut state = self.state.borrow_mut();
+        let PreviewState { camera, scene, .. } = &mut *state;
+        let (Some(camera), Some(scene)) = (camera.as_mut(), scene.as_ref()) else {
+            return;
+        };
+        if camera.sample_size >= PREVIEW_SAMPLES {
+            return;
+        }
+
+        camera.sample_size += 1;
+        camera.send_rays(scene.clone());
+        state.pixbuf = state.camera.as_ref().map(camera_pixbuf);
+        drop(state);
+        self.drawing_area.queue_draw();
+    }
+}
@@ -0,0 +1,136 @@
+use crate::gui::*;
+use crate::raytracer::{Camera, Tile};
+use gdk::prelude::GdkContextExt;
+use gdk_pixbuf::Colorspace;
+
+/// The camera of the last render, kept so parts of the image can be rendered again
+pub type LastRender = Rc<RefCell<Option<Camera>>>;
+
+/// A rectangle being dragged over the image, from where the mouse was pressed to where it is now
+type Selection = Rc<RefCell<Option<((f64, f64), (f64, f64))>>>;
+
+pub fn camera_pixbuf(camera: &Camera) -> Pixbuf {
+    let image = camera.image();
+    let bytes: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|color| [color.x, color.y, color.z])
+        .map(|channel| channel.round().clamp(0.0, 255.0) as u8)
+        .collect();
+    Pixbuf::from_mut_slice(
+        bytes,
+        Colorspace::Rgb,
+        false,
+        8,
+        image.width as i32,
+        image.height as i32,
+        image.width as i32 * 3,
+    )
+}
+
+/// The pixels covered by a selection, or `None` if it is empty
+fn selection_region(selection: ((f64, f64), (f64, f64)), camera: &Camera) -> Option<Tile> {
+    let ((start_x, start_y), (end_x, end_y)) = selection;
+    let (width, height) = camera.resolution;
+    let left = start_x.min(end_x).clamp(0.0, width as f64) as u32;
+    let top = start_y.min(end_y).clamp(0.0, height as f64) as u32;
+    let right = start_x.max(end_x).clamp(0.0, width as f64).ceil() as u32;
+    let bottom = start_y.max(end_y).clamp(0.0, height as f64).ceil() as u32;
+
+    (right > left && bottom > top).then_some(Tile {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    })
+}
+
+/// Show the last render in a window. Drag a rectangle over the image and press "Render region"
+/// to render just that part again with the current scene, keeping the rest of the image.
+pub fn show_render_window(app_state: Rc<RefCell<AppState>>, last_render: LastRender) {
+    let last_camera = last_render.borrow();
+    let Some(camera) = last_camera.as_ref() else {
+        return;
+    };
+    let (width, height) = camera.resolution;
+    let pixbuf = Rc::new(RefCell::new(camera_pixbuf(camera)));
+    let selection: Selection = Rc::new(RefCell::new(None));
+
+    let image_window = Window::new(WindowType::Toplevel);
+    image_window.set_title("Rendered Image");
+    image_window.set_default_size((width as i32 + 20).min(1200), (height as i32 + 60).min(900));
+
+    let vertical_box = GtkBox::new(Orientation::Vertical, 5);
+    let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+    let drawing_area = gtk::DrawingArea::new();
+    drawing_area.set_size_request(width as i32, height as i32);
+    drawing_area
+        .add_events(gdk::EventMask::BUTTON_PRESS_MASK | gdk::EventMask::BUTTON1_MOTION_MASK);
+    scrolled_window.add(&drawing_area);
+    vertical_box.pack_start(&scrolled_window, true, true, 0);
+
+    // Only available while a rectangle is selected
+    let render_region_button = Button::with_label("Render region");
+    render_region_button.set_sensitive(false);
+    render_region_button.set_tooltip_text(Some(
+        "Drag a rectangle over the image to pick the region to render",
+    ));
+    vertical_box.pack_start(&render_region_button, false, false, 0);
+    image_window.add(&vertical_box);
+
+    drawing_area.connect_draw(clone!(@strong pixbuf, @strong selection => move |_, cr| {
+        cr.set_source_pixbuf(&pixbuf.borrow(), 0.0, 0.0);
+        cr.paint();
+
+        if let Some(((start_x, start_y), (end_x, end_y))) = *selection.borrow() {
+            cr.set_source_rgba(1.0, 0.2, 0.2, 1.0);
+            cr.set_line_width(1.0);
+            cr.set_dash(&[4.0, 4.0], 0.0);
+            cr.rectangle(start_x, start_y, end_x - start_x, end_y - start_y);
+            cr.stroke();
+        }
+        Inhibit(false)
+    }));
+
+    drawing_area.connect_button_press_event(
+        clone!(@strong selection, @strong render_region_button => move |area, event| {
+            let position = event.get_position();
+            *selection.borrow_mut() = Some((position, position));
+            render_region_button.set_sensitive(true);
+            area.queue_draw();
+            Inhibit(true)
+        }),
+    );
+
+    drawing_area.connect_motion_notify_event(clone!(@strong selection => move |area, event| {
+        if let Some((_, end)) = selection.borrow_mut().as_mut() {
+            *end = event.get_position();
+        }
+        area.queue_draw();
+        Inhibit(true)
+    }));
+
+    render_region_button.connect_clicked(
+        clone!(@strong app_state, @strong last_render, @strong pixbuf, @strong selection, @strong drawing_area => move |button| {
+            button.set_sensitive(false);
+            let mut last_render = last_render.borrow_mut();
+            let Some(camera) = last_render.as_mut() else {
+                return;
+            };
+            // A click without dragging selects nothing
+            let Some(region) = selection.borrow_mut().take().and_then(|selection| selection_region(selection, camera)) else {
+                drawing_area.queue_draw();
+                return;
+            };
+
+            let scene = Arc::new(update_scene_from_gui(app_state.clone()));
+            camera.send_rays_in_region(scene, region);
+            camera.write_to_ppm("output.ppm");
+
+            *pixbuf.borrow_mut() = camera_pixbuf(camera);
+            drawing_area.queue_draw();
+        }),
+    );
+
+    image_window.show_all();
+}
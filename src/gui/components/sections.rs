@@ -0,0 +1,484 @@
+use crate::gui::components::*;
+use gtk::{FlowBoxChild, TargetEntry, TargetFlags};
+use std::cell::Cell;
+
+/// Drag and drop target for moving sections around in the object box
+const SECTION_TARGET: &str = "rt-section";
+
+/// The config of a section in the object box, kept in a list in `AppState`
+pub trait SectionConfig: Clone + 'static {
+    /// ID of the section, which is also the widget name of its grid
+    fn section_id(&self) -> String;
+    fn name_entry(&self) -> Entry;
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self>;
+    /// Add a new section of this kind, with the default values
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget;
+}
+
+/// Add a section and its config, with buttons to delete and duplicate it and a handle to drag it
+/// somewhere else in the object box. All of that can be undone, and so can every change made in
+/// the section.
+pub fn add_section<C: SectionConfig>(
+    app_state: &Rc<RefCell<AppState>>,
+    flow_box: &FlowBox,
+    grid: &gtk::Grid,
+    provider: &CssProvider,
+    delete_button: &Button,
+    config: C,
+) {
+    // For the highlight of `select_section`
+    grid.get_style_context()
+        .add_provider(provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+
+    let history = app_state.borrow().history.clone();
+    history.track(grid.upcast_ref());
+
+    let duplicate_button = Button::with_label("Duplicate");
+    grid.attach_next_to(
+        &duplicate_button,
+        Some(delete_button),
+        gtk::PositionType::Bottom,
+        1,
+        1,
+    );
+    attach_drag_handle(grid);
+
+    C::configs(&mut app_state.borrow_mut()).push(config.clone());
+    flow_box.add(grid);
+    flow_box.show_all();
+    history.record(section_edit(flow_box, grid, config.clone(), true, -1));
+
+    duplicate_button.connect_clicked(
+        clone!(@strong app_state, @strong flow_box, @weak grid, @strong config => move |_| {
+            duplicate_section(&app_state, &flow_box, &grid, &config);
+        }),
+    );
+
+    delete_button.connect_clicked(
+        clone!(@strong app_state, @strong flow_box, @weak grid => move |_| {
+            let id = config.section_id();
+            C::configs(&mut app_state.borrow_mut()).retain(|other| other.section_id() != id);
+            let position = remove_section(&flow_box, grid.upcast_ref());
+
+            let history = app_state.borrow().history.clone();
+            history.record(section_edit(&flow_box, &grid, config.clone(), false, position));
+        }),
+    );
+}
+
+fn section_edit<C: SectionConfig>(
+    flow_box: &FlowBox,
+    grid: &gtk::Grid,
+    config: C,
+    added: bool,
+    position: i32,
+) -> Edit {
+    let id = config.section_id();
+    Edit::Section {
+        flow_box: flow_box.clone(),
+        section: grid.clone().upcast(),
+        position: Cell::new(position),
+        insert: Box::new(move |app_state| C::configs(app_state).push(config.clone())),
+        remove: Box::new(move |app_state| {
+            C::configs(app_state).retain(|other| other.section_id() != id)
+        }),
+        added,
+    }
+}
+
+/// Add a new section with the values of `grid` right after it. The copy gets a new ID and the
+/// name of the original with "copy" added, and a number if that name is taken too.
+fn duplicate_section<C: SectionConfig>(
+    app_state: &Rc<RefCell<AppState>>,
+    flow_box: &FlowBox,
+    grid: &gtk::Grid,
+    config: &C,
+) {
+    let history = app_state.borrow().history.clone();
+    history.batch(|| {
+        let copy = C::create_section(app_state.clone(), flow_box.clone());
+        copy_values(grid.upcast_ref(), &copy);
+
+        let copy_name_entry = C::configs(&mut app_state.borrow_mut())
+            .last()
+            .map(C::name_entry);
+        if let Some(copy_name_entry) = copy_name_entry {
+            let name = copy_name(&app_state.borrow(), config.name_entry().get_text().trim());
+            copy_name_entry.set_text(&name);
+        }
+
+        move_section(
+            &history,
+            flow_box,
+            &copy,
+            section_position(grid.upcast_ref()) + 1,
+        );
+    });
+}
+
+/// "`name` copy", or "`name` copy 2", "`name` copy 3" and so on if another section has that
+/// name or ID already. Copies of sections without a name stay without one.
+fn copy_name(app_state: &AppState, name: &str) -> String {
+    if name.is_empty() {
+        return String::new();
+    }
+    let taken: Vec<String> = section_names(app_state)
+        .into_iter()
+        .flat_map(|(id, name)| [id, section_id(&name)])
+        .collect();
+    (1..)
+        .map(|number| match number {
+            1 => format!("{name} copy"),
+            _ => format!("{name} copy {number}"),
+        })
+        .find(|candidate| !taken.contains(&section_id(candidate)))
+        .unwrap_or_default()
+}
+
+/// Copy what was entered in every entry, selector and color button of `from` to the same
+/// widget in `to`, which has to be a section of the same kind
+fn copy_values(from: &gtk::Widget, to: &gtk::Widget) {
+    if let (Some(from), Some(to)) = (from.downcast_ref::<Entry>(), to.downcast_ref::<Entry>()) {
+        to.set_text(&from.get_text());
+    } else if let (Some(from), Some(to)) = (
+        from.downcast_ref::<ComboBoxText>(),
+        to.downcast_ref::<ComboBoxText>(),
+    ) {
+        to.set_active(from.get_active());
+    } else if let (Some(from), Some(to)) = (
+        from.downcast_ref::<gtk::ColorButton>(),
+        to.downcast_ref::<gtk::ColorButton>(),
+    ) {
+        to.set_rgba(&from.get_rgba());
+    } else if let (Some(from), Some(to)) = (
+        from.downcast_ref::<gtk::Container>(),
+        to.downcast_ref::<gtk::Container>(),
+    ) {
+        for (from, to) in from.get_children().iter().zip(to.get_children()) {
+            copy_values(from, &to);
+        }
+    }
+}
+
+fn section_target() -> TargetEntry {
+    TargetEntry::new(SECTION_TARGET, TargetFlags::SAME_APP, 0)
+}
+
+/// A handle at the top of the section to drag it to another place in the object box
+fn attach_drag_handle(grid: &gtk::Grid) {
+    let handle = gtk::EventBox::new();
+    handle.add(&gtk::Label::new(Some("☰")));
+    handle.set_tooltip_text(Some("Drag to move"));
+    grid.attach(&handle, 1, 0, 1, 1);
+
+    handle.drag_source_set(
+        gdk::ModifierType::BUTTON1_MASK,
+        &[section_target()],
+        gdk::DragAction::MOVE,
+    );
+    handle.connect_drag_data_get(clone!(@weak grid => move |_, _, selection_data, _, _| {
+        selection_data.set_text(&grid.get_widget_name());
+    }));
+}
+
+/// Let sections be dropped on the object box, moving them in front of the section they are
+/// dropped on
+pub fn accept_section_drops(app_state: &Rc<RefCell<AppState>>, flow_box: &FlowBox) {
+    flow_box.drag_dest_set(
+        gtk::DestDefaults::ALL,
+        &[section_target()],
+        gdk::DragAction::MOVE,
+    );
+    flow_box.connect_drag_data_received(
+        clone!(@strong app_state => move |flow_box, _, x, y, selection_data, _, _| {
+            let Some(section) = selection_data
+                .get_text()
+                .and_then(|id| find_section(flow_box, &id))
+            else {
+                return;
+            };
+            let mut position = child_at(flow_box, x, y).map_or(-1, |child| child.get_index());
+            // The target moves up one place once the section is taken out in front of it
+            if (0..position).contains(&section_position(&section)) {
+                position -= 1;
+            }
+
+            let history = app_state.borrow().history.clone();
+            move_section(&history, flow_box, &section, position);
+        }),
+    );
+}
+
+/// The child of `flow_box` at `x`, `y` in the coordinates of `flow_box`
+fn child_at(flow_box: &FlowBox, x: i32, y: i32) -> Option<FlowBoxChild> {
+    flow_box
+        .get_children()
+        .into_iter()
+        .filter_map(|child| child.downcast::<FlowBoxChild>().ok())
+        .find(|child| {
+            flow_box
+                .translate_coordinates(child, x, y)
+                .is_some_and(|(x, y)| {
+                    (0..child.get_allocated_width()).contains(&x)
+                        && (0..child.get_allocated_height()).contains(&y)
+                })
+        })
+}
+
+fn find_section(flow_box: &FlowBox, id: &str) -> Option<gtk::Widget> {
+    flow_box
+        .get_children()
+        .iter()
+        .filter_map(|child| child.downcast_ref::<FlowBoxChild>()?.get_child())
+        .find(|section| section.get_widget_name() == id)
+}
+
+/// Where `section` is in the object box, or -1 if it is not in it
+fn section_position(section: &gtk::Widget) -> i32 {
+    section
+        .get_parent()
+        .and_then(|parent| parent.downcast::<FlowBoxChild>().ok())
+        .map_or(-1, |flow_box_child| flow_box_child.get_index())
+}
+
+/// Move `section` to `position`, or to the end for -1, so it can be undone
+fn move_section(history: &History, flow_box: &FlowBox, section: &gtk::Widget, position: i32) {
+    let from = reposition_section(flow_box, section, position);
+    let to = section_position(section);
+    if from != to {
+        history.record(Edit::Move {
+            flow_box: flow_box.clone(),
+            section: section.clone(),
+            from,
+            to,
+        });
+    }
+}
+
+/// Move `section` to `position`, or to the end for -1, and return where it was
+pub fn reposition_section(flow_box: &FlowBox, section: &gtk::Widget, position: i32) -> i32 {
+    let from = remove_section(flow_box, section);
+    flow_box.insert(section, position);
+    flow_box.show_all();
+    from
+}
+
+/// Take `section` out of the object box, keeping it so it can be added back, and return where
+/// it was
+pub fn remove_section(flow_box: &FlowBox, section: &gtk::Widget) -> i32 {
+    let Some(flow_box_child) = section
+        .get_parent()
+        .and_then(|parent| parent.downcast::<FlowBoxChild>().ok())
+    else {
+        return -1;
+    };
+    let position = flow_box_child.get_index();
+    flow_box_child.remove(section);
+    flow_box.remove(&flow_box_child);
+    position
+}
+
+impl SectionConfig for SphereConfig {
+    fn section_id(&self) -> String {
+        format!("sphere_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.spheres
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_sphere_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for CylinderConfig {
+    fn section_id(&self) -> String {
+        format!("cylinder_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.cylinders
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_cylinder_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for CubeConfig {
+    fn section_id(&self) -> String {
+        format!("cube_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.cubes
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_cube_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for FlatPlaneConfig {
+    fn section_id(&self) -> String {
+        format!("flat_plane_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.flat_planes
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_flat_plane_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for ConeConfig {
+    fn section_id(&self) -> String {
+        format!("cone_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.cones
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_cone_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for TorusConfig {
+    fn section_id(&self) -> String {
+        format!("torus_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.tori
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_torus_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for CapsuleConfig {
+    fn section_id(&self) -> String {
+        format!("capsule_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.capsules
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_capsule_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for EllipsoidConfig {
+    fn section_id(&self) -> String {
+        format!("ellipsoid_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.ellipsoids
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_ellipsoid_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for GroupConfig {
+    fn section_id(&self) -> String {
+        format!("group_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.groups
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_group_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for PointLightConfig {
+    fn section_id(&self) -> String {
+        format!("point_light_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.point_lights
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_point_light_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for SpotLightConfig {
+    fn section_id(&self) -> String {
+        format!("spot_light_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.spot_lights
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_spot_light_section(app_state, flow_box)
+    }
+}
+impl SectionConfig for DirectionalLightConfig {
+    fn section_id(&self) -> String {
+        format!("directional_light_{}", *self.id.borrow())
+    }
+
+    fn name_entry(&self) -> Entry {
+        self.name_entry.borrow().clone()
+    }
+
+    fn configs(app_state: &mut AppState) -> &mut Vec<Self> {
+        &mut app_state.directional_lights
+    }
+
+    fn create_section(app_state: Rc<RefCell<AppState>>, flow_box: FlowBox) -> gtk::Widget {
+        create_directional_light_section(app_state, flow_box)
+    }
+}
@@ -0,0 +1,292 @@
+use crate::gui::components::{remove_section, reposition_section};
+use crate::gui::*;
+use gtk::ColorButton;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// Edits of the same widgets closer together than this are undone in one step, like typing a
+/// number or dragging a scale
+const MERGE_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Adds a config to or removes it from `AppState` when a section is added or deleted
+type ConfigChange = Box<dyn Fn(&mut AppState)>;
+
+/// A change made in the GUI, with what is needed to undo and redo it
+pub enum Edit {
+    Text {
+        entry: Entry,
+        before: String,
+        after: String,
+    },
+    Choice {
+        selector: ComboBoxText,
+        before: Option<u32>,
+        after: Option<u32>,
+    },
+    Color {
+        button: ColorButton,
+        before: gdk::RGBA,
+        after: gdk::RGBA,
+    },
+    Value {
+        scale: Scale,
+        before: f64,
+        after: f64,
+    },
+    /// A section added to the object box, or deleted from it if `added` is false. The section
+    /// is kept, so it comes back as it was, at the place it was.
+    Section {
+        flow_box: FlowBox,
+        section: gtk::Widget,
+        position: Cell<i32>,
+        insert: ConfigChange,
+        remove: ConfigChange,
+        added: bool,
+    },
+    /// A section dragged from position `from` in the object box to `to`
+    Move {
+        flow_box: FlowBox,
+        section: gtk::Widget,
+        from: i32,
+        to: i32,
+    },
+    /// Edits made together, like randomizing a section, undone in one step
+    Batch(Vec<Edit>),
+}
+
+impl Edit {
+    fn apply(&self, app_state: &Rc<RefCell<AppState>>, undo: bool) {
+        match self {
+            Edit::Text {
+                entry,
+                before,
+                after,
+            } => entry.set_text(if undo { before } else { after }),
+            Edit::Choice {
+                selector,
+                before,
+                after,
+            } => selector.set_active(if undo { *before } else { *after }),
+            Edit::Color {
+                button,
+                before,
+                after,
+            } => button.set_rgba(if undo { before } else { after }),
+            Edit::Value {
+                scale,
+                before,
+                after,
+            } => scale.set_value(if undo { *before } else { *after }),
+            Edit::Section {
+                flow_box,
+                section,
+                position,
+                insert,
+                remove,
+                added,
+            } => {
+                // Undoing an addition removes the section, undoing a deletion adds it back
+                if *added != undo {
+                    insert(&mut app_state.borrow_mut());
+                    flow_box.insert(section, position.get());
+                    flow_box.show_all();
+                } else {
+                    remove(&mut app_state.borrow_mut());
+                    position.set(remove_section(flow_box, section));
+                }
+            }
+            Edit::Move {
+                flow_box,
+                section,
+                from,
+                to,
+            } => {
+                reposition_section(flow_box, section, if undo { *from } else { *to });
+            }
+            Edit::Batch(edits) => {
+                if undo {
+                    edits
+                        .iter()
+                        .rev()
+                        .for_each(|edit| edit.apply(app_state, true));
+                } else {
+                    edits.iter().for_each(|edit| edit.apply(app_state, false));
+                }
+            }
+        }
+    }
+
+    /// Whether `next` changes the same widgets in the same way, so both can be one edit
+    fn same_target(&self, next: &Edit) -> bool {
+        match (self, next) {
+            (Edit::Text { entry, .. }, Edit::Text { entry: next, .. }) => entry == next,
+            (Edit::Choice { selector, .. }, Edit::Choice { selector: next, .. }) => {
+                selector == next
+            }
+            (Edit::Color { button, .. }, Edit::Color { button: next, .. }) => button == next,
+            (Edit::Value { scale, .. }, Edit::Value { scale: next, .. }) => scale == next,
+            (Edit::Batch(edits), Edit::Batch(next)) => {
+                edits.len() == next.len()
+                    && edits
+                        .iter()
+                        .zip(next)
+                        .all(|(edit, next)| edit.same_target(next))
+            }
+            _ => false,
+        }
+    }
+
+    /// Keep where this edit started from, and end where `next` ends
+    fn take_after(&mut self, next: &Edit) {
+        match (self, next) {
+            (Edit::Text { after, .. }, Edit::Text { after: next, .. }) => *after = next.clone(),
+            (Edit::Choice { after, .. }, Edit::Choice { after: next, .. }) => *after = *next,
+            (Edit::Color { after, .. }, Edit::Color { after: next, .. }) => *after = *next,
+            (Edit::Value { after, .. }, Edit::Value { after: next, .. }) => *after = *next,
+            (Edit::Batch(edits), Edit::Batch(next)) => {
+                for (edit, next) in edits.iter_mut().zip(next) {
+                    edit.take_after(next);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `History` keeps the edits made in the GUI so they can be undone with Ctrl+Z and redone with
+/// Ctrl+Shift+Z.
+///
+/// Widgets passed to `track` record their own changes. Adding, deleting, duplicating and moving
+/// sections is recorded by `add_section`. Callbacks given to `connect_changed` are called after
+/// every recorded, undone and redone edit.
+#[derive(Default)]
+pub struct History {
+    undo_stack: RefCell<Vec<Edit>>,
+    redo_stack: RefCell<Vec<Edit>>,
+    /// Edits collected by `batch`
+    batch: RefCell<Option<Vec<Edit>>>,
+    /// Set while undoing or redoing, so the widget changes that causes are not recorded again
+    applying: Cell<bool>,
+    last_recorded: Cell<Option<Instant>>,
+    on_change: RefCell<Vec<Box<dyn Fn()>>>,
+}
+
+impl History {
+    pub fn record(&self, edit: Edit) {
+        if self.applying.get() {
+            return;
+        }
+        if let Some(batch) = self.batch.borrow_mut().as_mut() {
+            batch.push(edit);
+            return;
+        }
+
+        self.redo_stack.borrow_mut().clear();
+        let now = Instant::now();
+        let is_recent = self
+            .last_recorded
+            .replace(Some(now))
+            .is_some_and(|last| now - last < MERGE_INTERVAL);
+
+        let mut undo_stack = self.undo_stack.borrow_mut();
+        match undo_stack.last_mut() {
+            Some(last) if is_recent && last.same_target(&edit) => last.take_after(&edit),
+            _ => undo_stack.push(edit),
+        }
+        drop(undo_stack);
+        self.changed();
+    }
+
+    /// Call `callback` whenever the GUI was edited, e.g. to keep a preview up to date
+    pub fn connect_changed(&self, callback: impl Fn() + 'static) {
+        self.on_change.borrow_mut().push(Box::new(callback));
+    }
+
+    fn changed(&self) {
+        for callback in self.on_change.borrow().iter() {
+            callback();
+        }
+    }
+
+    /// Run `edits` and record all the changes it makes as one edit
+    pub fn batch(&self, edits: impl FnOnce()) {
+        if self.batch.borrow().is_some() {
+            edits();
+            return;
+        }
+
+        *self.batch.borrow_mut() = Some(Vec::new());
+        edits();
+        let batch = self.batch.borrow_mut().take().unwrap_or_default();
+        if !batch.is_empty() {
+            self.record(Edit::Batch(batch));
+        }
+    }
+
+    pub fn undo(&self, app_state: &Rc<RefCell<AppState>>) {
+        let Some(edit) = self.undo_stack.borrow_mut().pop() else {
+            return;
+        };
+        self.apply(&edit, app_state, true);
+        self.redo_stack.borrow_mut().push(edit);
+    }
+
+    pub fn redo(&self, app_state: &Rc<RefCell<AppState>>) {
+        let Some(edit) = self.redo_stack.borrow_mut().pop() else {
+            return;
+        };
+        self.apply(&edit, app_state, false);
+        self.undo_stack.borrow_mut().push(edit);
+    }
+
+    fn apply(&self, edit: &Edit, app_state: &Rc<RefCell<AppState>>, undo: bool) {
+        self.applying.set(true);
+        edit.apply(app_state, undo);
+        self.applying.set(false);
+        // The next change starts a new edit instead of merging with this one
+        self.last_recorded.set(None);
+        self.changed();
+    }
+
+    /// Record the changes of `widget`, or of every entry, selector, color button and scale in it
+    pub fn track(self: &Rc<Self>, widget: &gtk::Widget) {
+        if let Some(entry) = widget.downcast_ref::<Entry>() {
+            let last = RefCell::new(entry.get_text().to_string());
+            entry.connect_changed(clone!(@weak self as history => move |entry| {
+                let after = entry.get_text().to_string();
+                let before = last.replace(after.clone());
+                history.record(Edit::Text { entry: entry.clone(), before, after });
+            }));
+        } else if let Some(selector) = widget.downcast_ref::<ComboBoxText>() {
+            let last = Cell::new(selector.get_active());
+            selector.connect_changed(clone!(@weak self as history => move |selector| {
+                let after = selector.get_active();
+                let before = last.replace(after);
+                history.record(Edit::Choice { selector: selector.clone(), before, after });
+            }));
+        } else if let Some(button) = widget.downcast_ref::<ColorButton>() {
+            let last = Cell::new(button.get_rgba());
+            // Unlike `connect_color_set`, this is also called when undo sets the color, which
+            // keeps `last` up to date
+            gtk::ColorChooserExt::connect_property_rgba_notify(
+                button,
+                clone!(@weak self as history => move |button| {
+                    let after = button.get_rgba();
+                    let before = last.replace(after);
+                    history.record(Edit::Color { button: button.clone(), before, after });
+                }),
+            );
+        } else if let Some(scale) = widget.downcast_ref::<Scale>() {
+            let last = Cell::new(scale.get_value());
+            scale.connect_value_changed(clone!(@weak self as history => move |scale| {
+                let after = scale.get_value();
+                let before = last.replace(after);
+                history.record(Edit::Value { scale: scale.clone(), before, after });
+            }));
+        } else if let Some(container) = widget.downcast_ref::<gtk::Container>() {
+            for child in container.get_children() {
+                self.track(&child);
+            }
+        }
+    }
+}
@@ -9,7 +9,17 @@ pub fn launch_gui() {
         cylinders: Vec::new(),
         cubes: Vec::new(),
         flat_planes: Vec::new(),
+        cones: Vec::new(),
+        tori: Vec::new(),
+        capsules: Vec::new(),
+        ellipsoids: Vec::new(),
+        groups: Vec::new(),
+        point_lights: Vec::new(),
+        spot_lights: Vec::new(),
+        directional_lights: Vec::new(),
         brightness: 0.5,
+        history: Rc::new(History::default()),
+        section_numbers: HashMap::new(),
     }));
 
     gtk::init().expect("Failed to initialize GTK.");
@@ -71,15 +81,16 @@ pub fn launch_gui() {
     brightness_scale.set_value(0.5);
     brightness_scale.set_digits(2);
 
-    // Focal length
-    let focal_length_box = gtk::Box::new(Orientation::Vertical, 0);
-    let adjustment = gtk::Adjustment::new(1.0, 0.01, 2.0, 0.01, 0.01, 0.0);
-    let focal_length_scale = horizontal_scale("Focal length", adjustment, &focal_length_box);
-    focal_length_scale.set_value(1.0);
-    focal_length_scale.set_digits(2);
+    // Field of view, 53.13 degrees is the same as a focal length of 1.0
+    let field_of_view_box = gtk::Box::new(Orientation::Vertical, 0);
+    let adjustment = gtk::Adjustment::new(53.13, 5.0, 170.0, 0.1, 1.0, 0.0);
+    let field_of_view_scale =
+        horizontal_scale("Field of view (degrees)", adjustment, &field_of_view_box);
+    field_of_view_scale.set_value(53.13);
+    field_of_view_scale.set_digits(1);
 
     dual_scales.pack_start(&brightness_box, true, true, 0);
-    dual_scales.pack_start(&focal_length_box, true, true, 0);
+    dual_scales.pack_start(&field_of_view_box, true, true, 0);
     vertical_box.pack_start(&dual_scales, false, true, 0);
 
     let app_state_clone = app_state.clone();
@@ -110,11 +121,44 @@ pub fn launch_gui() {
     // Resolution Selection
     let (width_entry, height_entry) = add_resolution_box(&vertical_box);
 
+    // Camera settings can be undone like the objects
+    let history = app_state.borrow().history.clone();
+    for widget in [
+        camera_box.upcast_ref::<gtk::Widget>(),
+        width_entry.upcast_ref(),
+        height_entry.upcast_ref(),
+        brightness_scale.upcast_ref(),
+        field_of_view_scale.upcast_ref(),
+    ] {
+        history.track(widget);
+    }
+
+    // Live preview with mouse and keyboard navigation
+    let preview = Preview::new(
+        &vertical_box,
+        app_state.clone(),
+        CameraEntries {
+            position: [
+                cam_x_entry.clone(),
+                cam_y_entry.clone(),
+                cam_z_entry.clone(),
+            ],
+            look_at: [
+                look_at_x_entry.clone(),
+                look_at_y_entry.clone(),
+                look_at_z_entry.clone(),
+            ],
+            width: width_entry.clone(),
+            height: height_entry.clone(),
+        },
+        field_of_view_scale.clone(),
+    );
+
     separator(&vertical_box, 10);
 
     // Create a horizontal box for the side-by-side buttons
     let button_box = gtk::Box::new(Orientation::Horizontal, 5);
-    let (add_sphere_btn, add_cylinder_btn, add_cube_btn, add_plane_btn) = create_buttons_row(
+    let [add_sphere_btn, add_cylinder_btn, add_cube_btn, add_plane_btn] = create_buttons_row(
         &button_box,
         &provider,
         ["Add Sphere", "Add Cylinder", "Add Cube", "Add Flat Plane"],
@@ -123,10 +167,40 @@ pub fn launch_gui() {
     // Add the button box to the vertical box
     vertical_box.pack_start(&button_box, false, false, 0);
 
+    let shape_button_box = gtk::Box::new(Orientation::Horizontal, 5);
+    let [add_cone_btn, add_torus_btn, add_capsule_btn, add_ellipsoid_btn, add_group_btn] =
+        create_buttons_row(
+            &shape_button_box,
+            &provider,
+            [
+                "Add Cone",
+                "Add Torus",
+                "Add Capsule",
+                "Add Ellipsoid",
+                "Add Group",
+            ],
+        );
+    vertical_box.pack_start(&shape_button_box, false, false, 0);
+
+    // Light sources get their own row below the objects
+    let light_button_box = gtk::Box::new(Orientation::Horizontal, 5);
+    let [add_point_light_btn, add_spot_light_btn, add_directional_light_btn] = create_buttons_row(
+        &light_button_box,
+        &provider,
+        ["Add Point Light", "Add Spot Light", "Add Directional Light"],
+    );
+    vertical_box.pack_start(&light_button_box, false, false, 0);
+
     separator(&vertical_box, 10);
 
     // Create a flow box for all the objects
     let object_box = create_object_box(&vertical_box);
+    accept_section_drops(&app_state, &object_box);
+
+    // Clicking on an object in the preview highlights its section
+    preview.connect_selected(clone!(@strong object_box => move |id| {
+        select_section(&object_box, id);
+    }));
 
     add_sphere_btn.connect_clicked(clone!(@strong object_box, @strong app_state => move |_| {
         create_sphere_section(app_state.clone(), object_box.clone());
@@ -144,6 +218,40 @@ pub fn launch_gui() {
         create_flat_plane_section(app_state.clone(), object_box.clone());
     }));
 
+    add_cone_btn.connect_clicked(clone!(@strong object_box, @strong app_state => move |_| {
+        create_cone_section(app_state.clone(), object_box.clone());
+    }));
+
+    add_torus_btn.connect_clicked(clone!(@strong object_box, @strong app_state => move |_| {
+        create_torus_section(app_state.clone(), object_box.clone());
+    }));
+
+    add_capsule_btn.connect_clicked(clone!(@strong object_box, @strong app_state => move |_| {
+        create_capsule_section(app_state.clone(), object_box.clone());
+    }));
+
+    add_ellipsoid_btn.connect_clicked(clone!(@strong object_box, @strong app_state => move |_| {
+        create_ellipsoid_section(app_state.clone(), object_box.clone());
+    }));
+
+    add_group_btn.connect_clicked(clone!(@strong object_box, @strong app_state => move |_| {
+        create_group_section(app_state.clone(), object_box.clone());
+    }));
+
+    add_point_light_btn.connect_clicked(clone!(@strong object_box, @strong app_state => move |_| {
+        create_point_light_section(app_state.clone(), object_box.clone());
+    }));
+
+    add_spot_light_btn.connect_clicked(clone!(@strong object_box, @strong app_state => move |_| {
+        create_spot_light_section(app_state.clone(), object_box.clone());
+    }));
+
+    add_directional_light_btn.connect_clicked(
+        clone!(@strong object_box, @strong app_state => move |_| {
+            create_directional_light_section(app_state.clone(), object_box.clone());
+        }),
+    );
+
     // Create and add the render button to the vertical box
     let render_button = create_button_with_label("Render", &provider);
     vertical_box.pack_start(&render_button, false, false, 0);
@@ -164,7 +272,15 @@ pub fn launch_gui() {
     let show_image_button = Button::with_label("Show Image");
     vertical_box.pack_start(&show_image_button, false, false, 0);
 
-    show_image_button.connect_clicked(move |_| {
+    let last_render: LastRender = Rc::new(RefCell::new(None));
+
+    show_image_button.connect_clicked(clone!(@strong app_state, @strong last_render => move |_| {
+        if last_render.borrow().is_some() {
+            show_render_window(app_state.clone(), last_render.clone());
+            return;
+        }
+
+        // Nothing rendered since the GUI was opened, show the image of an earlier run
         let image_window = Window::new(WindowType::Toplevel);
         image_window.set_title("Rendered Image");
         image_window.set_default_size(400, 400); // Set to your desired size
@@ -173,10 +289,10 @@ pub fn launch_gui() {
         image_window.add(&image);
 
         image_window.show_all();
-    });
+    }));
 
     // Render Button
-    render_button.connect_clicked(clone!(@strong app_state, @strong message_label => move |_| {
+    render_button.connect_clicked(clone!(@strong app_state, @strong message_label, @strong last_render => move |_| {
         let app_state_borrowed = app_state.borrow();
         let mut all_inputs_valid = true;
 
@@ -200,6 +316,54 @@ pub fn launch_gui() {
             return;
         }
 
+        if !validate_cones(&app_state_borrowed.cones) {
+            message_label.set_markup("<span foreground='red'>Invalid cone detected.</span>");
+            return;
+        }
+
+        if !validate_tori(&app_state_borrowed.tori) {
+            message_label.set_markup("<span foreground='red'>Invalid torus detected.</span>");
+            return;
+        }
+
+        if !validate_capsules(&app_state_borrowed.capsules) {
+            message_label.set_markup("<span foreground='red'>Invalid capsule detected.</span>");
+            return;
+        }
+
+        if !validate_ellipsoids(&app_state_borrowed.ellipsoids) {
+            message_label.set_markup("<span foreground='red'>Invalid ellipsoid detected.</span>");
+            return;
+        }
+
+        if !validate_groups(&app_state_borrowed.groups) {
+            message_label.set_markup("<span foreground='red'>Invalid group detected.</span>");
+            return;
+        }
+
+        if !validate_point_lights(&app_state_borrowed.point_lights) {
+            message_label.set_markup("<span foreground='red'>Invalid point light detected.</span>");
+            return;
+        }
+
+        if !validate_spot_lights(&app_state_borrowed.spot_lights) {
+            message_label.set_markup("<span foreground='red'>Invalid spot light detected.</span>");
+            return;
+        }
+
+        if !validate_directional_lights(&app_state_borrowed.directional_lights) {
+            message_label.set_markup("<span foreground='red'>Invalid directional light detected.</span>");
+            return;
+        }
+
+        if let Err(name) = validate_section_names(&app_state_borrowed) {
+            message_label.set_markup(&format!(
+                "<span foreground='red'>More than one section is called {}.</span>",
+                glib::markup_escape_text(&name)
+            ));
+            return;
+        }
+
         let mut cam_x = 0.0;
         let mut cam_y = 0.0;
         let mut cam_z = 0.0;
@@ -211,7 +375,7 @@ pub fn launch_gui() {
 
 
         let sample_size = sample_size_scale.get_value() as u16;
-        let focal_length = focal_length_scale.get_value();
+        let field_of_view = field_of_view_scale.get_value();
         if let (Ok(x), Ok(y), Ok(z), Ok(look_x), Ok(look_y), Ok(look_z), Ok(w), Ok(h)) = (
             cam_x_entry.get_text().parse::<f64>(),
             cam_y_entry.get_text().parse::<f64>(),
@@ -240,7 +404,7 @@ pub fn launch_gui() {
             message_label.set_markup(green_style);
 
             // Schedule rendering to start after a short delay
-            glib::timeout_add_local(50, clone!(@strong app_state => move || {
+            glib::timeout_add_local(50, clone!(@strong app_state, @strong last_render => move || {
                 const OUTPUT_PATH: &str = "output.ppm";
                 let updated_scene = Arc::new(update_scene_from_gui(app_state.clone()));
 
@@ -248,13 +412,13 @@ pub fn launch_gui() {
                 .sample_size(sample_size)
                 .position_by_coordinates(Vector3::new(cam_x, cam_y, cam_z))
                 .look_at(Vector3::new(look_at_x, look_at_y, look_at_z))
-                .focal_length(focal_length)
+                .vertical_field_of_view(field_of_view)
                 .resolution(width, height)
-                .sensor_width(1.0)
                 .build();
 
                 camera.send_rays(updated_scene);
                 camera.write_to_ppm(OUTPUT_PATH);
+                *last_render.borrow_mut() = Some(camera);
                 message_label_clone.set_markup(render_done_style);
 
                 glib::Continue(false)
@@ -265,6 +429,23 @@ pub fn launch_gui() {
         }
     }));
 
+    // Ctrl+Z undoes the last edit, Ctrl+Shift+Z redoes it
+    window.connect_key_press_event(clone!(@strong app_state => move |_, event| {
+        let modifiers = event.get_state();
+        let is_z = event.get_keyval().to_unicode().map(|key| key.to_ascii_lowercase()) == Some('z');
+        if !is_z || !modifiers.contains(gdk::ModifierType::CONTROL_MASK) {
+            return Inhibit(false);
+        }
+
+        let history = app_state.borrow().history.clone();
+        if modifiers.contains(gdk::ModifierType::SHIFT_MASK) {
+            history.redo(&app_state);
+        } else {
+            history.undo(&app_state);
+        }
+        Inhibit(true)
+    }));
+
     window.connect_delete_event(|_, _| {
         gtk::main_quit();
         Inhibit(false)
@@ -1,15 +1,32 @@
-use crate::gui::AppState;
-use crate::objects::{Cube, Cylinder, FlatPlane, Objects, Sphere};
+use crate::gui::components::SectionConfig;
+use crate::gui::{AppState, ComboBoxText, Entry, GroupConfig, HashMap};
+use crate::lights::{DirectionalLight, Falloff, Lights, PointLight, SpotLight};
+use crate::objects::{
+    Capsule, Cone, Cube, Cylinder, Ellipsoid, FlatPlane, Group, Object, Objects, Sphere, Torus,
+    Transform,
+};
 use crate::raytracer::Scene;
+use crate::textures::Procedural as ProceduralTexture;
 use crate::textures::Texture::*;
+use crate::textures::{Emission, Pattern, Texture};
 use crate::type_aliases::Color;
 use gtk::{ColorChooserExt, ComboBoxTextExt, EntryExt};
 
 use crate::gui::{Arc, Rc, RefCell, Vector3};
 
+/// Every object in the scene with the ID of the GUI section it came from
+pub type SectionObjects = Vec<(String, Arc<dyn Object>)>;
+
 pub fn update_scene_from_gui(app_state: Rc<RefCell<AppState>>) -> Scene {
+    scene_with_sections_from_gui(app_state).0
+}
+
+/// The scene, and which section every object came from, to find the section of an object the
+/// user clicked on with `Scene::hit_object`
+pub fn scene_with_sections_from_gui(app_state: Rc<RefCell<AppState>>) -> (Scene, SectionObjects) {
     let app_state_borrowed = app_state.borrow();
-    let mut objects: Objects = Vec::new();
+    // Objects with the ID of their section, so groups can find them
+    let mut objects: Vec<(String, Arc<dyn Object>)> = Vec::new();
 
     // Creating Spheres
     for sphere_config in app_state_borrowed.spheres.iter() {
@@ -50,13 +67,24 @@ pub fn update_scene_from_gui(app_state: Rc<RefCell<AppState>>) -> Scene {
         let sphere_texture = match material.as_str() {
             "Diffusive" => Diffusive(sphere_color),
             "Reflective" => Reflective,
-            "Light" => Light(sphere_color),
+            "Light" => Light(emission(
+                sphere_color,
+                &sphere_config.strength_entry,
+                &sphere_config.temperature_entry,
+            )),
+            "Checkerboard" | "Noise" | "Marble" | "Wood" => Procedural(procedural(
+                &material,
+                sphere_color,
+                &sphere_config.pattern_scale_entry,
+                &sphere_config.second_color_button,
+            )),
             // Add other cases as needed
             _ => Diffusive(sphere_color), // Default case
         };
 
         let sphere = Sphere::new(Vector3::new(pos_x, pos_y, pos_z), radius, sphere_texture);
-        objects.push(Arc::new(sphere));
+        let id = format!("sphere_{}", *sphere_config.id.borrow());
+        objects.push((id, Arc::new(sphere)));
     }
 
     // Creating Cylinders
@@ -103,7 +131,17 @@ pub fn update_scene_from_gui(app_state: Rc<RefCell<AppState>>) -> Scene {
         let cylinder_texture = match material.as_str() {
             "Diffusive" => Diffusive(cylinder_color),
             "Reflective" => Reflective,
-            "Light" => Light(cylinder_color),
+            "Light" => Light(emission(
+                cylinder_color,
+                &cylinder_config.strength_entry,
+                &cylinder_config.temperature_entry,
+            )),
+            "Checkerboard" | "Noise" | "Marble" | "Wood" => Procedural(procedural(
+                &material,
+                cylinder_color,
+                &cylinder_config.pattern_scale_entry,
+                &cylinder_config.second_color_button,
+            )),
             // Add other cases as needed
             _ => Diffusive(cylinder_color), // Default case
         };
@@ -114,7 +152,8 @@ pub fn update_scene_from_gui(app_state: Rc<RefCell<AppState>>) -> Scene {
             height,
             cylinder_texture,
         );
-        objects.push(Arc::new(cylinder));
+        let id = format!("cylinder_{}", *cylinder_config.id.borrow());
+        objects.push((id, Arc::new(cylinder)));
     }
 
     // Creating Cubes
@@ -155,13 +194,24 @@ pub fn update_scene_from_gui(app_state: Rc<RefCell<AppState>>) -> Scene {
         let cube_texture = match material.as_str() {
             "Diffusive" => Diffusive(cube_color),
             "Reflective" => Reflective,
-            "Light" => Light(cube_color),
+            "Light" => Light(emission(
+                cube_color,
+                &cube_config.strength_entry,
+                &cube_config.temperature_entry,
+            )),
+            "Checkerboard" | "Noise" | "Marble" | "Wood" => Procedural(procedural(
+                &material,
+                cube_color,
+                &cube_config.pattern_scale_entry,
+                &cube_config.second_color_button,
+            )),
             // Add other cases as needed
             _ => Diffusive(cube_color), // Default case
         };
 
         let cube = Cube::new(Vector3::new(pos_x, pos_y, pos_z), radius, cube_texture);
-        objects.push(Arc::new(cube));
+        let id = format!("cube_{}", *cube_config.id.borrow());
+        objects.push((id, Arc::new(cube)));
     }
 
     // Creating Flat Planes
@@ -202,7 +252,17 @@ pub fn update_scene_from_gui(app_state: Rc<RefCell<AppState>>) -> Scene {
         let flat_plane_texture = match material.as_str() {
             "Diffusive" => Diffusive(flat_plane_color),
             "Reflective" => Reflective,
-            "Light" => Light(flat_plane_color),
+            "Light" => Light(emission(
+                flat_plane_color,
+                &flat_plane_config.strength_entry,
+                &flat_plane_config.temperature_entry,
+            )),
+            "Checkerboard" | "Noise" | "Marble" | "Wood" => Procedural(procedural(
+                &material,
+                flat_plane_color,
+                &flat_plane_config.pattern_scale_entry,
+                &flat_plane_config.second_color_button,
+            )),
             // Add other cases as needed
             _ => Diffusive(flat_plane_color), // Default case
         };
@@ -212,11 +272,416 @@ pub fn update_scene_from_gui(app_state: Rc<RefCell<AppState>>) -> Scene {
             radius,
             flat_plane_texture,
         );
-        objects.push(Arc::new(flat_plane));
+        let id = format!("flat_plane_{}", *flat_plane_config.id.borrow());
+        objects.push((id, Arc::new(flat_plane)));
+    }
+
+    // Creating Cones
+    for cone_config in app_state_borrowed.cones.iter() {
+        let cone = Cone::new(
+            Vector3::new(
+                entry_value(&cone_config.pos_x_entry, 0.0),
+                entry_value(&cone_config.pos_y_entry, 0.0),
+                entry_value(&cone_config.pos_z_entry, 0.0),
+            ),
+            entry_value(&cone_config.radius_entry, 1.0),
+            entry_value(&cone_config.top_radius_entry, 0.0),
+            entry_value(&cone_config.height_entry, 2.0),
+            material_texture(
+                &cone_config.material_selector,
+                &cone_config.color_button,
+                &cone_config.strength_entry,
+                &cone_config.temperature_entry,
+                &cone_config.pattern_scale_entry,
+                &cone_config.second_color_button,
+            ),
+        );
+        let id = format!("cone_{}", *cone_config.id.borrow());
+        objects.push((id, Arc::new(cone)));
+    }
+
+    // Creating Tori
+    for torus_config in app_state_borrowed.tori.iter() {
+        let torus = Torus::new(
+            Vector3::new(
+                entry_value(&torus_config.pos_x_entry, 0.0),
+                entry_value(&torus_config.pos_y_entry, 0.0),
+                entry_value(&torus_config.pos_z_entry, 0.0),
+            ),
+            entry_value(&torus_config.major_radius_entry, 2.0),
+            entry_value(&torus_config.minor_radius_entry, 0.5),
+            material_texture(
+                &torus_config.material_selector,
+                &torus_config.color_button,
+                &torus_config.strength_entry,
+                &torus_config.temperature_entry,
+                &torus_config.pattern_scale_entry,
+                &torus_config.second_color_button,
+            ),
+        );
+        let id = format!("torus_{}", *torus_config.id.borrow());
+        objects.push((id, Arc::new(torus)));
+    }
+
+    // Creating Capsules
+    for capsule_config in app_state_borrowed.capsules.iter() {
+        let capsule = Capsule::new(
+            Vector3::new(
+                entry_value(&capsule_config.pos_x_entry, 0.0),
+                entry_value(&capsule_config.pos_y_entry, 0.0),
+                entry_value(&capsule_config.pos_z_entry, 0.0),
+            ),
+            entry_value(&capsule_config.radius_entry, 0.5),
+            entry_value(&capsule_config.height_entry, 2.0),
+            material_texture(
+                &capsule_config.material_selector,
+                &capsule_config.color_button,
+                &capsule_config.strength_entry,
+                &capsule_config.temperature_entry,
+                &capsule_config.pattern_scale_entry,
+                &capsule_config.second_color_button,
+            ),
+        );
+        let id = format!("capsule_{}", *capsule_config.id.borrow());
+        objects.push((id, Arc::new(capsule)));
     }
 
-    Scene {
+    // Creating Ellipsoids
+    for ellipsoid_config in app_state_borrowed.ellipsoids.iter() {
+        let ellipsoid = Ellipsoid::new(
+            Vector3::new(
+                entry_value(&ellipsoid_config.pos_x_entry, 0.0),
+                entry_value(&ellipsoid_config.pos_y_entry, 0.0),
+                entry_value(&ellipsoid_config.pos_z_entry, 0.0),
+            ),
+            Vector3::new(
+                entry_value(&ellipsoid_config.radius_x_entry, 1.0),
+                entry_value(&ellipsoid_config.radius_y_entry, 1.0),
+                entry_value(&ellipsoid_config.radius_z_entry, 1.0),
+            ),
+            material_texture(
+                &ellipsoid_config.material_selector,
+                &ellipsoid_config.color_button,
+                &ellipsoid_config.strength_entry,
+                &ellipsoid_config.temperature_entry,
+                &ellipsoid_config.pattern_scale_entry,
+                &ellipsoid_config.second_color_button,
+            ),
+        );
+        let id = format!("ellipsoid_{}", *ellipsoid_config.id.borrow());
+        objects.push((id, Arc::new(ellipsoid)));
+    }
+
+    let mut lights: Lights = Vec::new();
+
+    // Creating Point Lights
+    for point_light_config in app_state_borrowed.point_lights.iter() {
+        let point_light = PointLight::new(
+            Vector3::new(
+                entry_value(&point_light_config.pos_x_entry, 0.0),
+                entry_value(&point_light_config.pos_y_entry, 0.0),
+                entry_value(&point_light_config.pos_z_entry, 0.0),
+            ),
+            button_color(&point_light_config.color_button),
+            entry_value(&point_light_config.intensity_entry, 1.0),
+            selected_falloff(&point_light_config.falloff_selector),
+        );
+        lights.push(Arc::new(point_light));
+    }
+
+    // Creating Spot Lights
+    for spot_light_config in app_state_borrowed.spot_lights.iter() {
+        let spot_light = SpotLight::new(
+            Vector3::new(
+                entry_value(&spot_light_config.pos_x_entry, 0.0),
+                entry_value(&spot_light_config.pos_y_entry, 0.0),
+                entry_value(&spot_light_config.pos_z_entry, 0.0),
+            ),
+            Vector3::new(
+                entry_value(&spot_light_config.dir_x_entry, 0.0),
+                entry_value(&spot_light_config.dir_y_entry, -1.0),
+                entry_value(&spot_light_config.dir_z_entry, 0.0),
+            ),
+            button_color(&spot_light_config.color_button),
+            entry_value(&spot_light_config.intensity_entry, 1.0),
+            selected_falloff(&spot_light_config.falloff_selector),
+            entry_value(&spot_light_config.inner_angle_entry, 20.0),
+            entry_value(&spot_light_config.outer_angle_entry, 30.0),
+        );
+        lights.push(Arc::new(spot_light));
+    }
+
+    // Creating Directional Lights
+    for directional_light_config in app_state_borrowed.directional_lights.iter() {
+        let directional_light = DirectionalLight::new(
+            Vector3::new(
+                entry_value(&directional_light_config.dir_x_entry, 0.0),
+                entry_value(&directional_light_config.dir_y_entry, -1.0),
+                entry_value(&directional_light_config.dir_z_entry, 0.0),
+            ),
+            button_color(&directional_light_config.color_button),
+            entry_value(&directional_light_config.intensity_entry, 1.0),
+        );
+        lights.push(Arc::new(directional_light));
+    }
+
+    let sections = objects.clone();
+    let section_ids = section_ids_by_name(&app_state_borrowed);
+    let (objects, groups) = build_groups(objects, &app_state_borrowed.groups, &section_ids);
+
+    let scene = Scene {
         objects,
+        groups,
+        lights,
+        fog: None,
         brightness: app_state_borrowed.brightness,
+    };
+    (scene, sections)
+}
+
+/// The name of a section as groups refer to it, e.g. "Flat Plane 2" becomes "flat_plane_2",
+/// which is also the ID of a section that was not renamed
+pub fn section_id(name: &str) -> String {
+    name.trim().to_lowercase().replace(' ', "_")
+}
+
+/// The ID and the name of every object and group section, in the order of the sections of each
+/// kind. Sections that were not given a name have an empty one.
+pub fn section_names(app_state: &AppState) -> Vec<(String, String)> {
+    fn add<C: SectionConfig>(sections: &mut Vec<(String, String)>, configs: &[C]) {
+        for config in configs {
+            let name = config.name_entry().get_text().trim().to_string();
+            sections.push((config.section_id(), name));
+        }
+    }
+
+    let mut sections = Vec::new();
+    add(&mut sections, &app_state.spheres);
+    add(&mut sections, &app_state.cylinders);
+    add(&mut sections, &app_state.cubes);
+    add(&mut sections, &app_state.flat_planes);
+    add(&mut sections, &app_state.cones);
+    add(&mut sections, &app_state.tori);
+    add(&mut sections, &app_state.capsules);
+    add(&mut sections, &app_state.ellipsoids);
+    add(&mut sections, &app_state.groups);
+    sections
+}
+
+/// The ID of every object and group section by its name, so groups can refer to sections by the
+/// names the user gave them. `validate_section_names` makes sure no name is used twice.
+fn section_ids_by_name(app_state: &AppState) -> HashMap<String, String> {
+    let mut section_ids = HashMap::new();
+    for (id, name) in section_names(app_state) {
+        section_ids.entry(section_id(&name)).or_insert(id);
+    }
+    section_ids
+}
+
+/// Move the members of every group out of `objects` and into the group. Groups that are members
+/// of another group are nested inside it.
+fn build_groups(
+    mut objects: Vec<(String, Arc<dyn Object>)>,
+    group_configs: &[GroupConfig],
+    section_ids: &HashMap<String, String>,
+) -> (Objects, Vec<Group>) {
+    let ids: Vec<String> = group_configs
+        .iter()
+        .map(SectionConfig::section_id)
+        .collect();
+    let names: Vec<String> = group_configs
+        .iter()
+        .map(|config| config.name_entry.borrow().get_text().trim().to_string())
+        .collect();
+    let members: Vec<Vec<String>> = group_configs
+        .iter()
+        .map(|config| {
+            config
+                .members_entry
+                .borrow()
+                .get_text()
+                .split(',')
+                .map(section_id)
+                .filter(|member| !member.is_empty())
+                // Members are section names, or the IDs of sections without another name
+                .map(|member| section_ids.get(&member).cloned().unwrap_or(member))
+                .collect()
+        })
+        .collect();
+
+    let is_nested = |index: usize| {
+        members
+            .iter()
+            .enumerate()
+            .any(|(other, members)| other != index && members.contains(&ids[index]))
+    };
+
+    // Groups that are in no other group come first. Groups that only contain each other are
+    // left over after that, and the first of them is built with the others inside it.
+    let mut groups = Vec::new();
+    let mut built = Vec::new();
+    for index in (0..group_configs.len())
+        .filter(|&index| !is_nested(index))
+        .chain(0..group_configs.len())
+    {
+        if built.contains(&index) {
+            continue;
+        }
+        let mut visited = Vec::new();
+        groups.push(build_group(
+            index,
+            group_configs,
+            &ids,
+            &names,
+            &members,
+            &mut objects,
+            &mut visited,
+        ));
+        built.extend(visited);
+    }
+
+    (
+        objects.into_iter().map(|(_, object)| object).collect(),
+        groups,
+    )
+}
+
+fn build_group(
+    index: usize,
+    group_configs: &[GroupConfig],
+    ids: &[String],
+    names: &[String],
+    members: &[Vec<String>],
+    objects: &mut Vec<(String, Arc<dyn Object>)>,
+    visited: &mut Vec<usize>,
+) -> Group {
+    visited.push(index);
+    let config = &group_configs[index];
+    let transform = Transform::from_parts(
+        Vector3::new(
+            entry_value(&config.pos_x_entry, 0.0),
+            entry_value(&config.pos_y_entry, 0.0),
+            entry_value(&config.pos_z_entry, 0.0),
+        ),
+        Vector3::new(
+            entry_value(&config.rot_x_entry, 0.0),
+            entry_value(&config.rot_y_entry, 0.0),
+            entry_value(&config.rot_z_entry, 0.0),
+        ),
+        Vector3::new(1.0, 1.0, 1.0),
+    );
+
+    let mut group = Group::new(names[index].clone(), transform);
+    for member in &members[index] {
+        if let Some(position) = objects.iter().position(|(id, _)| id == member) {
+            group = group.with_object(objects.remove(position).1);
+        } else if let Some(child) = ids.iter().position(|id| id == member) {
+            // A group can not contain itself
+            if visited.contains(&child) {
+                eprintln!(
+                    "Group {}: {member} contains this group or is already in it",
+                    names[index]
+                );
+            } else {
+                group = group.with_group(build_group(
+                    child,
+                    group_configs,
+                    ids,
+                    names,
+                    members,
+                    objects,
+                    visited,
+                ));
+            }
+        } else {
+            eprintln!("Group {}: no object or group called {member}", names[index]);
+        }
+    }
+    group
+}
+
+/// Parse the number in `entry`, or use `default` if it is empty or invalid
+fn entry_value(entry: &Rc<RefCell<Entry>>, default: f64) -> f64 {
+    entry.borrow().get_text().parse::<f64>().unwrap_or(default)
+}
+
+/// Texture from the material widgets of an object section
+fn material_texture(
+    material_selector: &Rc<RefCell<ComboBoxText>>,
+    color_button: &Rc<RefCell<gtk::ColorButton>>,
+    strength_entry: &Rc<RefCell<Entry>>,
+    temperature_entry: &Rc<RefCell<Entry>>,
+    pattern_scale_entry: &Rc<RefCell<Entry>>,
+    second_color_button: &Rc<RefCell<gtk::ColorButton>>,
+) -> Texture {
+    let material = material_selector
+        .borrow()
+        .get_active_text()
+        .unwrap_or_else(|| "DefaultMaterial".into());
+    let color = button_color(color_button);
+
+    match material.as_str() {
+        "Reflective" => Reflective,
+        "Light" => Light(emission(color, strength_entry, temperature_entry)),
+        "Checkerboard" | "Noise" | "Marble" | "Wood" => Procedural(procedural(
+            &material,
+            color,
+            pattern_scale_entry,
+            second_color_button,
+        )),
+        _ => Diffusive(color),
+    }
+}
+
+/// Emission of a "Light" material. An empty temperature entry means `color` is used.
+fn emission(
+    color: Color,
+    strength_entry: &Rc<RefCell<Entry>>,
+    temperature_entry: &Rc<RefCell<Entry>>,
+) -> Emission {
+    let strength = entry_value(strength_entry, 1.0);
+    match temperature_entry.borrow().get_text().parse::<f64>() {
+        Ok(temperature) => Emission::blackbody(temperature, strength),
+        Err(_) => Emission::new(color, strength),
+    }
+}
+
+/// Procedural texture blending from `color` to the second color button
+fn procedural(
+    material: &str,
+    color: Color,
+    scale_entry: &Rc<RefCell<Entry>>,
+    second_color_button: &Rc<RefCell<gtk::ColorButton>>,
+) -> ProceduralTexture {
+    let pattern = match material {
+        "Noise" => Pattern::Noise,
+        "Marble" => Pattern::Marble,
+        "Wood" => Pattern::Wood,
+        _ => Pattern::Checkerboard,
+    };
+
+    ProceduralTexture::new(
+        pattern,
+        entry_value(scale_entry, 1.0),
+        color,
+        button_color(second_color_button),
+    )
+}
+
+fn button_color(color_button: &Rc<RefCell<gtk::ColorButton>>) -> Color {
+    let color = color_button.borrow().get_rgba();
+    Color::new(color.red * 255., color.green * 255., color.blue * 255.)
+}
+
+fn selected_falloff(falloff_selector: &Rc<RefCell<ComboBoxText>>) -> Falloff {
+    let falloff = falloff_selector
+        .borrow()
+        .get_active_text()
+        .unwrap_or_else(|| "Quadratic".into());
+
+    match falloff.as_str() {
+        "None" => Falloff::None,
+        "Linear" => Falloff::Linear,
+        _ => Falloff::Quadratic,
     }
 }
@@ -1,4 +1,8 @@
-use crate::gui::{CubeConfig, CylinderConfig, FlatPlaneConfig, SphereConfig};
+use crate::gui::{
+    section_id, section_names, AppState, CapsuleConfig, ConeConfig, CubeConfig, CylinderConfig,
+    DirectionalLightConfig, EllipsoidConfig, FlatPlaneConfig, GroupConfig, PointLightConfig,
+    SphereConfig, SpotLightConfig, TorusConfig,
+};
 use gtk::EntryExt;
 
 pub fn validate_spheres(spheres: &[SphereConfig]) -> bool {
@@ -7,11 +11,17 @@ pub fn validate_spheres(spheres: &[SphereConfig]) -> bool {
         let pos_y = sphere.pos_y_entry.borrow().get_text().to_string();
         let pos_z = sphere.pos_z_entry.borrow().get_text().to_string();
         let radius = sphere.radius_entry.borrow().get_text().to_string();
+        let strength = sphere.strength_entry.borrow().get_text().to_string();
+        let temperature = sphere.temperature_entry.borrow().get_text().to_string();
+        let pattern_scale = sphere.pattern_scale_entry.borrow().get_text().to_string();
 
         if !is_valid_number(&pos_x)
             || !is_valid_number(&pos_y)
             || !is_valid_number(&pos_z)
             || !is_valid_number(&radius)
+            || !is_valid_number(&strength)
+            || !is_valid_number(&temperature)
+            || !is_valid_number(&pattern_scale)
         {
             return false;
         }
@@ -25,6 +35,9 @@ pub fn validate_cylinders(cylinders: &[CylinderConfig]) -> bool {
         let pos_y = cylinder.pos_y_entry.borrow().get_text().to_string();
         let pos_z = cylinder.pos_z_entry.borrow().get_text().to_string();
         let radius = cylinder.radius_entry.borrow().get_text().to_string();
+        let strength = cylinder.strength_entry.borrow().get_text().to_string();
+        let temperature = cylinder.temperature_entry.borrow().get_text().to_string();
+        let pattern_scale = cylinder.pattern_scale_entry.borrow().get_text().to_string();
         let height = cylinder.height_entry.borrow().get_text().to_string();
 
         if !is_valid_number(&pos_x)
@@ -32,6 +45,9 @@ pub fn validate_cylinders(cylinders: &[CylinderConfig]) -> bool {
             || !is_valid_number(&pos_z)
             || !is_valid_number(&radius)
             || !is_valid_number(&height)
+            || !is_valid_number(&strength)
+            || !is_valid_number(&temperature)
+            || !is_valid_number(&pattern_scale)
         {
             return false;
         }
@@ -45,11 +61,17 @@ pub fn validate_cubes(cubes: &[CubeConfig]) -> bool {
         let pos_y = cube.pos_y_entry.borrow().get_text().to_string();
         let pos_z = cube.pos_z_entry.borrow().get_text().to_string();
         let radius = cube.radius_entry.borrow().get_text().to_string();
+        let strength = cube.strength_entry.borrow().get_text().to_string();
+        let temperature = cube.temperature_entry.borrow().get_text().to_string();
+        let pattern_scale = cube.pattern_scale_entry.borrow().get_text().to_string();
 
         if !is_valid_number(&pos_x)
             || !is_valid_number(&pos_y)
             || !is_valid_number(&pos_z)
             || !is_valid_number(&radius)
+            || !is_valid_number(&strength)
+            || !is_valid_number(&temperature)
+            || !is_valid_number(&pattern_scale)
         {
             return false;
         }
@@ -63,11 +85,21 @@ pub fn validate_flat_planes(flat_planes: &[FlatPlaneConfig]) -> bool {
         let pos_y = flat_plane.pos_y_entry.borrow().get_text().to_string();
         let pos_z = flat_plane.pos_z_entry.borrow().get_text().to_string();
         let radius = flat_plane.radius_entry.borrow().get_text().to_string();
+        let strength = flat_plane.strength_entry.borrow().get_text().to_string();
+        let temperature = flat_plane.temperature_entry.borrow().get_text().to_string();
+        let pattern_scale = flat_plane
+            .pattern_scale_entry
+            .borrow()
+            .get_text()
+            .to_string();
 
         if !is_valid_number(&pos_x)
             || !is_valid_number(&pos_y)
             || !is_valid_number(&pos_z)
             || !is_valid_number(&radius)
+            || !is_valid_number(&strength)
+            || !is_valid_number(&temperature)
+            || !is_valid_number(&pattern_scale)
         {
             return false;
         }
@@ -75,6 +107,256 @@ pub fn validate_flat_planes(flat_planes: &[FlatPlaneConfig]) -> bool {
     true
 }
 
+pub fn validate_cones(cones: &[ConeConfig]) -> bool {
+    for cone in cones {
+        let pos_x = cone.pos_x_entry.borrow().get_text().to_string();
+        let pos_y = cone.pos_y_entry.borrow().get_text().to_string();
+        let pos_z = cone.pos_z_entry.borrow().get_text().to_string();
+        let radius = cone.radius_entry.borrow().get_text().to_string();
+        let top_radius = cone.top_radius_entry.borrow().get_text().to_string();
+        let height = cone.height_entry.borrow().get_text().to_string();
+        let strength = cone.strength_entry.borrow().get_text().to_string();
+        let temperature = cone.temperature_entry.borrow().get_text().to_string();
+        let pattern_scale = cone.pattern_scale_entry.borrow().get_text().to_string();
+
+        if !is_valid_number(&pos_x)
+            || !is_valid_number(&pos_y)
+            || !is_valid_number(&pos_z)
+            || !is_valid_size(&radius)
+            || !is_valid_non_negative(&top_radius)
+            || !is_valid_size(&height)
+            || !is_valid_number(&strength)
+            || !is_valid_number(&temperature)
+            || !is_valid_number(&pattern_scale)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn validate_tori(tori: &[TorusConfig]) -> bool {
+    for torus in tori {
+        let pos_x = torus.pos_x_entry.borrow().get_text().to_string();
+        let pos_y = torus.pos_y_entry.borrow().get_text().to_string();
+        let pos_z = torus.pos_z_entry.borrow().get_text().to_string();
+        let major_radius = torus.major_radius_entry.borrow().get_text().to_string();
+        let minor_radius = torus.minor_radius_entry.borrow().get_text().to_string();
+        let strength = torus.strength_entry.borrow().get_text().to_string();
+        let temperature = torus.temperature_entry.borrow().get_text().to_string();
+        let pattern_scale = torus.pattern_scale_entry.borrow().get_text().to_string();
+
+        if !is_valid_number(&pos_x)
+            || !is_valid_number(&pos_y)
+            || !is_valid_number(&pos_z)
+            || !is_valid_size(&major_radius)
+            || !is_valid_size(&minor_radius)
+            || !is_valid_number(&strength)
+            || !is_valid_number(&temperature)
+            || !is_valid_number(&pattern_scale)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn validate_capsules(capsules: &[CapsuleConfig]) -> bool {
+    for capsule in capsules {
+        let pos_x = capsule.pos_x_entry.borrow().get_text().to_string();
+        let pos_y = capsule.pos_y_entry.borrow().get_text().to_string();
+        let pos_z = capsule.pos_z_entry.borrow().get_text().to_string();
+        let radius = capsule.radius_entry.borrow().get_text().to_string();
+        let height = capsule.height_entry.borrow().get_text().to_string();
+        let strength = capsule.strength_entry.borrow().get_text().to_string();
+        let temperature = capsule.temperature_entry.borrow().get_text().to_string();
+        let pattern_scale = capsule.pattern_scale_entry.borrow().get_text().to_string();
+
+        if !is_valid_number(&pos_x)
+            || !is_valid_number(&pos_y)
+            || !is_valid_number(&pos_z)
+            || !is_valid_size(&radius)
+            || !is_valid_size(&height)
+            || !is_valid_number(&strength)
+            || !is_valid_number(&temperature)
+            || !is_valid_number(&pattern_scale)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn validate_ellipsoids(ellipsoids: &[EllipsoidConfig]) -> bool {
+    for ellipsoid in ellipsoids {
+        let pos_x = ellipsoid.pos_x_entry.borrow().get_text().to_string();
+        let pos_y = ellipsoid.pos_y_entry.borrow().get_text().to_string();
+        let pos_z = ellipsoid.pos_z_entry.borrow().get_text().to_string();
+        let radius_x = ellipsoid.radius_x_entry.borrow().get_text().to_string();
+        let radius_y = ellipsoid.radius_y_entry.borrow().get_text().to_string();
+        let radius_z = ellipsoid.radius_z_entry.borrow().get_text().to_string();
+        let strength = ellipsoid.strength_entry.borrow().get_text().to_string();
+        let temperature = ellipsoid.temperature_entry.borrow().get_text().to_string();
+        let pattern_scale = ellipsoid
+            .pattern_scale_entry
+            .borrow()
+            .get_text()
+            .to_string();
+
+        if !is_valid_number(&pos_x)
+            || !is_valid_number(&pos_y)
+            || !is_valid_number(&pos_z)
+            || !is_valid_size(&radius_x)
+            || !is_valid_size(&radius_y)
+            || !is_valid_size(&radius_z)
+            || !is_valid_number(&strength)
+            || !is_valid_number(&temperature)
+            || !is_valid_number(&pattern_scale)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn validate_groups(groups: &[GroupConfig]) -> bool {
+    for group in groups {
+        let name = group.name_entry.borrow().get_text().to_string();
+        let pos_x = group.pos_x_entry.borrow().get_text().to_string();
+        let pos_y = group.pos_y_entry.borrow().get_text().to_string();
+        let pos_z = group.pos_z_entry.borrow().get_text().to_string();
+        let rot_x = group.rot_x_entry.borrow().get_text().to_string();
+        let rot_y = group.rot_y_entry.borrow().get_text().to_string();
+        let rot_z = group.rot_z_entry.borrow().get_text().to_string();
+
+        if name.trim().is_empty()
+            || !is_valid_number(&pos_x)
+            || !is_valid_number(&pos_y)
+            || !is_valid_number(&pos_z)
+            || !is_valid_number(&rot_x)
+            || !is_valid_number(&rot_y)
+            || !is_valid_number(&rot_z)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// Groups find their members by name, so a name may neither be used by two sections nor be the
+/// ID of another section. Returns the first name that is taken.
+pub fn validate_section_names(app_state: &AppState) -> Result<(), String> {
+    let sections = section_names(app_state);
+    for (index, (_, name)) in sections.iter().enumerate() {
+        let key = section_id(name);
+        if key.is_empty() {
+            continue;
+        }
+        let is_taken = sections
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != index)
+            .any(|(_, (other_id, other_name))| *other_id == key || section_id(other_name) == key);
+        if is_taken {
+            return Err(name.clone());
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_point_lights(point_lights: &[PointLightConfig]) -> bool {
+    for point_light in point_lights {
+        let pos_x = point_light.pos_x_entry.borrow().get_text().to_string();
+        let pos_y = point_light.pos_y_entry.borrow().get_text().to_string();
+        let pos_z = point_light.pos_z_entry.borrow().get_text().to_string();
+        let intensity = point_light.intensity_entry.borrow().get_text().to_string();
+
+        if !is_valid_number(&pos_x)
+            || !is_valid_number(&pos_y)
+            || !is_valid_number(&pos_z)
+            || !is_valid_number(&intensity)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn validate_spot_lights(spot_lights: &[SpotLightConfig]) -> bool {
+    for spot_light in spot_lights {
+        let pos_x = spot_light.pos_x_entry.borrow().get_text().to_string();
+        let pos_y = spot_light.pos_y_entry.borrow().get_text().to_string();
+        let pos_z = spot_light.pos_z_entry.borrow().get_text().to_string();
+        let dir_x = spot_light.dir_x_entry.borrow().get_text().to_string();
+        let dir_y = spot_light.dir_y_entry.borrow().get_text().to_string();
+        let dir_z = spot_light.dir_z_entry.borrow().get_text().to_string();
+        let intensity = spot_light.intensity_entry.borrow().get_text().to_string();
+        let inner_angle = spot_light.inner_angle_entry.borrow().get_text().to_string();
+        let outer_angle = spot_light.outer_angle_entry.borrow().get_text().to_string();
+
+        if !is_valid_number(&pos_x)
+            || !is_valid_number(&pos_y)
+            || !is_valid_number(&pos_z)
+            || !is_valid_direction(&dir_x, &dir_y, &dir_z)
+            || !is_valid_number(&intensity)
+            || !is_valid_number(&inner_angle)
+            || !is_valid_number(&outer_angle)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn validate_directional_lights(directional_lights: &[DirectionalLightConfig]) -> bool {
+    for directional_light in directional_lights {
+        let dir_x = directional_light
+            .dir_x_entry
+            .borrow()
+            .get_text()
+            .to_string();
+        let dir_y = directional_light
+            .dir_y_entry
+            .borrow()
+            .get_text()
+            .to_string();
+        let dir_z = directional_light
+            .dir_z_entry
+            .borrow()
+            .get_text()
+            .to_string();
+        let intensity = directional_light
+            .intensity_entry
+            .borrow()
+            .get_text()
+            .to_string();
+
+        if !is_valid_direction(&dir_x, &dir_y, &dir_z) || !is_valid_number(&intensity) {
+            return false;
+        }
+    }
+    true
+}
+
+// A direction can not be normalized if all components are zero
+fn is_valid_direction(x: &str, y: &str, z: &str) -> bool {
+    let components = [x, y, z];
+    components.iter().all(|c| is_valid_number(c))
+        && components
+            .iter()
+            .any(|c| c.parse::<f64>().is_ok_and(|value| value != 0.0))
+}
+
+// Radii and heights must be positive, or the solvers divide by zero and find nothing
+fn is_valid_size(input: &str) -> bool {
+    input.is_empty() || input.parse::<f64>().is_ok_and(|value| value > 0.0)
+}
+
+// A cone may narrow to a point, but not past it
+fn is_valid_non_negative(input: &str) -> bool {
+    input.is_empty() || input.parse::<f64>().is_ok_and(|value| value >= 0.0)
+}
+
 // Function to validate position entries
 fn is_valid_number(input: &str) -> bool {
     input.is_empty() || input.parse::<f64>().is_ok()
@@ -14,19 +14,24 @@ pub mod config {
     /// Configurations for `camera.rs`
     pub mod camera {
         pub use crate::color::RGB;
-        pub use crate::raytracer::{Ray, Scene};
+        pub use crate::raytracer::{create_tiles, Ray, RenderState, Scene, Tile, TileOrder};
         pub use crate::type_aliases::{Pixels, Point, Resolution};
         pub use nalgebra::Vector3;
-        pub use rand::Rng;
+        pub use rand::rngs::StdRng;
+        pub use rand::{Rng, SeedableRng};
         pub use rayon::prelude::*;
         pub use std::io::Write;
-        pub use std::sync::Arc;
+        pub use std::sync::{Arc, Mutex};
+        pub use std::time::{Duration, Instant};
 
         pub const DEFAULT_CAMERA_POSITION: Point = Point::new(1.0, 0.5, 0.0);
         pub const DEFAULT_SAMPLE_SIZE: u16 = 1000;
         pub const DEFAULT_FOCAL_LENGTH: f64 = 1.0;
-        pub const DEFAULT_SENSOR_WIDTH: f64 = 1.0;
+        /// Width of a 35mm film frame in millimetres
+        pub const DEFAULT_SENSOR_WIDTH: f64 = 36.0;
         pub const DEFAULT_RESOLUTION: Resolution = (800, 600);
+        pub const DEFAULT_TILE_SIZE: u32 = 32;
+        pub const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
     }
 }
 
@@ -75,6 +80,10 @@ pub mod type_aliases {
     pub type Pixels = Vec<Color>;
 
     pub type Resolution = (u32, u32);
+
+    /// `Uv` is a position on the surface of an object, used for looking up image textures.
+    /// Both coordinates go from 0.0 to 1.0 over the surface.
+    pub type Uv = nalgebra::Vector2<f64>;
 }
 
 pub mod color {
@@ -325,6 +334,22 @@ pub mod color {
 
             total
         }
+
+        /// The light from the scene's light sources that is reflected towards the camera.
+        ///
+        /// Light arriving at a collision is tinted by the color of that surface and every
+        /// surface before it along the path.
+        pub fn direct_lighting(&self) -> crate::type_aliases::Color {
+            let mut throughput = Vector3::new(1.0, 1.0, 1.0);
+            let mut total = Vector3::default();
+
+            for (color, light) in self.collisions.iter().zip(&self.direct_light) {
+                throughput.component_mul_assign(&(color / 255.));
+                total += light.component_mul(&throughput);
+            }
+
+            total
+        }
     }
 }
 
@@ -339,6 +364,7 @@ pub mod gui {
     };
     pub use nalgebra::Vector3;
     pub use std::cell::RefCell;
+    pub use std::collections::HashMap;
     pub use std::rc::Rc;
     pub use std::sync::Arc;
 
@@ -347,21 +373,49 @@ pub mod gui {
         pub cylinders: Vec<CylinderConfig>,
         pub cubes: Vec<CubeConfig>,
         pub flat_planes: Vec<FlatPlaneConfig>,
+        pub cones: Vec<ConeConfig>,
+        pub tori: Vec<TorusConfig>,
+        pub capsules: Vec<CapsuleConfig>,
+        pub ellipsoids: Vec<EllipsoidConfig>,
+        pub groups: Vec<GroupConfig>,
+        pub point_lights: Vec<PointLightConfig>,
+        pub spot_lights: Vec<SpotLightConfig>,
+        pub directional_lights: Vec<DirectionalLightConfig>,
         pub brightness: f64,
+        pub history: Rc<History>,
+        /// The last number given to a section of each kind. Numbers are not reused after a
+        /// section is deleted, so every section keeps a unique ID.
+        pub section_numbers: HashMap<&'static str, u32>,
     }
 
+    impl AppState {
+        /// Number for a new section of `kind`, e.g. 3 for "sphere_3"
+        pub fn next_section_number(&mut self, kind: &'static str) -> u32 {
+            let number = self.section_numbers.entry(kind).or_insert(0);
+            *number += 1;
+            *number
+        }
+    }
+
+    #[derive(Clone)]
     pub struct SphereConfig {
         pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
         pub pos_x_entry: Rc<RefCell<Entry>>,
         pub pos_y_entry: Rc<RefCell<Entry>>,
         pub pos_z_entry: Rc<RefCell<Entry>>,
         pub radius_entry: Rc<RefCell<Entry>>,
         pub material_selector: Rc<RefCell<ComboBoxText>>,
         pub color_button: Rc<RefCell<gtk::ColorButton>>,
+        pub strength_entry: Rc<RefCell<Entry>>,
+        pub temperature_entry: Rc<RefCell<Entry>>,
+        pub pattern_scale_entry: Rc<RefCell<Entry>>,
+        pub second_color_button: Rc<RefCell<gtk::ColorButton>>,
     }
     #[derive(Clone)]
     pub struct CylinderConfig {
         pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
         pub pos_x_entry: Rc<RefCell<Entry>>,
         pub pos_y_entry: Rc<RefCell<Entry>>,
         pub pos_z_entry: Rc<RefCell<Entry>>,
@@ -369,31 +423,175 @@ pub mod gui {
         pub material_selector: Rc<RefCell<ComboBoxText>>,
         pub height_entry: Rc<RefCell<Entry>>,
         pub color_button: Rc<RefCell<gtk::ColorButton>>,
+        pub strength_entry: Rc<RefCell<Entry>>,
+        pub temperature_entry: Rc<RefCell<Entry>>,
+        pub pattern_scale_entry: Rc<RefCell<Entry>>,
+        pub second_color_button: Rc<RefCell<gtk::ColorButton>>,
     }
 
+    #[derive(Clone)]
     pub struct CubeConfig {
         pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
         pub pos_x_entry: Rc<RefCell<Entry>>,
         pub pos_y_entry: Rc<RefCell<Entry>>,
         pub pos_z_entry: Rc<RefCell<Entry>>,
         pub radius_entry: Rc<RefCell<Entry>>,
         pub material_selector: Rc<RefCell<ComboBoxText>>,
         pub color_button: Rc<RefCell<gtk::ColorButton>>,
+        pub strength_entry: Rc<RefCell<Entry>>,
+        pub temperature_entry: Rc<RefCell<Entry>>,
+        pub pattern_scale_entry: Rc<RefCell<Entry>>,
+        pub second_color_button: Rc<RefCell<gtk::ColorButton>>,
     }
 
+    #[derive(Clone)]
     pub struct FlatPlaneConfig {
         pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
         pub pos_x_entry: Rc<RefCell<Entry>>,
         pub pos_y_entry: Rc<RefCell<Entry>>,
         pub pos_z_entry: Rc<RefCell<Entry>>,
         pub radius_entry: Rc<RefCell<Entry>>,
         pub material_selector: Rc<RefCell<ComboBoxText>>,
         pub color_button: Rc<RefCell<gtk::ColorButton>>,
+        pub strength_entry: Rc<RefCell<Entry>>,
+        pub temperature_entry: Rc<RefCell<Entry>>,
+        pub pattern_scale_entry: Rc<RefCell<Entry>>,
+        pub second_color_button: Rc<RefCell<gtk::ColorButton>>,
+    }
+
+    #[derive(Clone)]
+    pub struct ConeConfig {
+        pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
+        pub pos_x_entry: Rc<RefCell<Entry>>,
+        pub pos_y_entry: Rc<RefCell<Entry>>,
+        pub pos_z_entry: Rc<RefCell<Entry>>,
+        pub radius_entry: Rc<RefCell<Entry>>,
+        pub top_radius_entry: Rc<RefCell<Entry>>,
+        pub height_entry: Rc<RefCell<Entry>>,
+        pub material_selector: Rc<RefCell<ComboBoxText>>,
+        pub color_button: Rc<RefCell<gtk::ColorButton>>,
+        pub strength_entry: Rc<RefCell<Entry>>,
+        pub temperature_entry: Rc<RefCell<Entry>>,
+        pub pattern_scale_entry: Rc<RefCell<Entry>>,
+        pub second_color_button: Rc<RefCell<gtk::ColorButton>>,
+    }
+
+    #[derive(Clone)]
+    pub struct TorusConfig {
+        pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
+        pub pos_x_entry: Rc<RefCell<Entry>>,
+        pub pos_y_entry: Rc<RefCell<Entry>>,
+        pub pos_z_entry: Rc<RefCell<Entry>>,
+        pub major_radius_entry: Rc<RefCell<Entry>>,
+        pub minor_radius_entry: Rc<RefCell<Entry>>,
+        pub material_selector: Rc<RefCell<ComboBoxText>>,
+        pub color_button: Rc<RefCell<gtk::ColorButton>>,
+        pub strength_entry: Rc<RefCell<Entry>>,
+        pub temperature_entry: Rc<RefCell<Entry>>,
+        pub pattern_scale_entry: Rc<RefCell<Entry>>,
+        pub second_color_button: Rc<RefCell<gtk::ColorButton>>,
+    }
+
+    #[derive(Clone)]
+    pub struct CapsuleConfig {
+        pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
+        pub pos_x_entry: Rc<RefCell<Entry>>,
+        pub pos_y_entry: Rc<RefCell<Entry>>,
+        pub pos_z_entry: Rc<RefCell<Entry>>,
+        pub radius_entry: Rc<RefCell<Entry>>,
+        pub height_entry: Rc<RefCell<Entry>>,
+        pub material_selector: Rc<RefCell<ComboBoxText>>,
+        pub color_button: Rc<RefCell<gtk::ColorButton>>,
+        pub strength_entry: Rc<RefCell<Entry>>,
+        pub temperature_entry: Rc<RefCell<Entry>>,
+        pub pattern_scale_entry: Rc<RefCell<Entry>>,
+        pub second_color_button: Rc<RefCell<gtk::ColorButton>>,
+    }
+
+    #[derive(Clone)]
+    pub struct EllipsoidConfig {
+        pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
+        pub pos_x_entry: Rc<RefCell<Entry>>,
+        pub pos_y_entry: Rc<RefCell<Entry>>,
+        pub pos_z_entry: Rc<RefCell<Entry>>,
+        pub radius_x_entry: Rc<RefCell<Entry>>,
+        pub radius_y_entry: Rc<RefCell<Entry>>,
+        pub radius_z_entry: Rc<RefCell<Entry>>,
+        pub material_selector: Rc<RefCell<ComboBoxText>>,
+        pub color_button: Rc<RefCell<gtk::ColorButton>>,
+        pub strength_entry: Rc<RefCell<Entry>>,
+        pub temperature_entry: Rc<RefCell<Entry>>,
+        pub pattern_scale_entry: Rc<RefCell<Entry>>,
+        pub second_color_button: Rc<RefCell<gtk::ColorButton>>,
+    }
+
+    /// A named group. `members_entry` lists the objects and groups in it by the title of their
+    /// section, separated by commas.
+    #[derive(Clone)]
+    pub struct GroupConfig {
+        pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
+        pub pos_x_entry: Rc<RefCell<Entry>>,
+        pub pos_y_entry: Rc<RefCell<Entry>>,
+        pub pos_z_entry: Rc<RefCell<Entry>>,
+        pub rot_x_entry: Rc<RefCell<Entry>>,
+        pub rot_y_entry: Rc<RefCell<Entry>>,
+        pub rot_z_entry: Rc<RefCell<Entry>>,
+        pub members_entry: Rc<RefCell<Entry>>,
+    }
+
+    #[derive(Clone)]
+    pub struct PointLightConfig {
+        pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
+        pub pos_x_entry: Rc<RefCell<Entry>>,
+        pub pos_y_entry: Rc<RefCell<Entry>>,
+        pub pos_z_entry: Rc<RefCell<Entry>>,
+        pub intensity_entry: Rc<RefCell<Entry>>,
+        pub falloff_selector: Rc<RefCell<ComboBoxText>>,
+        pub color_button: Rc<RefCell<gtk::ColorButton>>,
+    }
+
+    #[derive(Clone)]
+    pub struct SpotLightConfig {
+        pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
+        pub pos_x_entry: Rc<RefCell<Entry>>,
+        pub pos_y_entry: Rc<RefCell<Entry>>,
+        pub pos_z_entry: Rc<RefCell<Entry>>,
+        pub dir_x_entry: Rc<RefCell<Entry>>,
+        pub dir_y_entry: Rc<RefCell<Entry>>,
+        pub dir_z_entry: Rc<RefCell<Entry>>,
+        pub intensity_entry: Rc<RefCell<Entry>>,
+        pub inner_angle_entry: Rc<RefCell<Entry>>,
+        pub outer_angle_entry: Rc<RefCell<Entry>>,
+        pub falloff_selector: Rc<RefCell<ComboBoxText>>,
+        pub color_button: Rc<RefCell<gtk::ColorButton>>,
+    }
+
+    #[derive(Clone)]
+    pub struct DirectionalLightConfig {
+        pub id: Rc<RefCell<u32>>,
+        pub name_entry: Rc<RefCell<Entry>>,
+        pub dir_x_entry: Rc<RefCell<Entry>>,
+        pub dir_y_entry: Rc<RefCell<Entry>>,
+        pub dir_z_entry: Rc<RefCell<Entry>>,
+        pub intensity_entry: Rc<RefCell<Entry>>,
+        pub color_button: Rc<RefCell<gtk::ColorButton>>,
     }
 
     pub mod interface;
     pub use interface::*;
 
+    pub mod history;
+    pub use history::*;
+
     pub mod update;
     pub use update::*;
 
@@ -409,6 +607,9 @@ pub mod gui {
         pub mod objects;
         pub use objects::*;
 
+        pub mod lights;
+        pub use lights::*;
+
         pub mod buttons;
         pub use buttons::*;
 
@@ -417,17 +618,36 @@ pub mod gui {
 
         pub mod about;
         pub use about::*;
+
+        pub mod preview;
+        pub use preview::*;
+
+        pub mod render_view;
+        pub use render_view::*;
+
+        pub mod sections;
+        pub use sections::*;
     }
 }
 
 pub mod raytracer {
 
+    pub mod animation;
+    pub use animation::*;
     pub mod camera;
     pub use camera::*;
+    pub mod checkpoint;
+    pub use checkpoint::*;
+    pub mod navigation;
+    pub use navigation::*;
     pub mod ray;
     pub use ray::*;
     pub mod scene;
     pub use scene::*;
+    pub mod stereo;
+    pub use stereo::*;
+    pub mod tiles;
+    pub use tiles::*;
 }
 
 pub mod objects {
@@ -441,9 +661,36 @@ pub mod objects {
     pub use flat_plane::*;
 
     pub mod sphere;
+    pub mod volume;
+    pub use volume::*;
+    pub mod normal_mapped;
+    pub use normal_mapped::*;
+    pub mod csg;
+    pub use csg::*;
+    pub mod cone;
+    pub use cone::*;
+    pub mod torus;
+    pub use torus::*;
+    pub mod capsule;
+    pub use capsule::*;
+    pub mod ellipsoid;
+    pub use ellipsoid::*;
+    pub mod distance_field;
+    pub use distance_field::*;
+    pub mod heightfield;
+    pub use heightfield::*;
+    pub mod transform;
+    pub use transform::*;
+    pub mod instance;
+    pub use instance::*;
+    pub mod group;
+    pub use group::*;
+    pub mod moving;
+    pub use moving::*;
+
     use crate::raytracer::Ray;
     use crate::textures::Texture;
-    use crate::type_aliases::{Normal, Point};
+    use crate::type_aliases::{Direction, Normal, Point, Uv};
     pub use sphere::*;
 
     /// [Discriminant equation](https://en.wikipedia.org/wiki/Discriminant)
@@ -461,38 +708,231 @@ pub mod objects {
     pub trait Object: Send + Sync {
         fn intersection(&self, ray: &Ray) -> Option<Intersection>;
         fn texture(&self) -> Texture;
+
+        /// Every part of the ray that is inside the object, sorted by distance. Used by `Csg`
+        /// to combine objects.
+        ///
+        /// The default finds them by following the ray through the object one hit at a time.
+        fn spans(&self, ray: &Ray) -> Vec<Span> {
+            probe_spans(self, ray)
+        }
     }
 
     pub type Objects = Vec<Arc<dyn Object>>;
 
     pub type Distance = f64;
 
+    /// The part of a ray between entering and leaving an object. If the ray starts inside the
+    /// object, `enter` is at the origin of the ray.
+    #[derive(Clone)]
+    pub struct Span {
+        pub enter: Intersection,
+        pub exit: Intersection,
+    }
+
+    #[derive(Clone)]
     pub struct Intersection {
         pub hit_point: Point,
+        /// The hit point relative to the object, so procedural textures move with the object
+        pub local_point: Point,
+        pub uv: Uv,
         pub normal: Normal,
+        /// Direction on the surface in which `uv.x` grows
+        pub tangent: Direction,
+        /// Direction on the surface in which `uv.y` grows
+        pub bitangent: Direction,
         pub distance: Distance,
         pub texture: Texture,
     }
 
     impl Intersection {
         pub fn new(hit_point: Point, normal: Normal, distance: Distance, texture: Texture) -> Self {
+            let (tangent, bitangent) = tangent_frame(normal);
             Self {
                 hit_point,
+                local_point: hit_point,
+                uv: Uv::zeros(),
                 normal,
+                tangent,
+                bitangent,
                 distance,
                 texture,
             }
         }
+
+        pub fn with_local_point(mut self, local_point: Point) -> Self {
+            self.local_point = local_point;
+            self
+        }
+
+        pub fn with_uv(mut self, uv: Uv) -> Self {
+            self.uv = uv;
+            self
+        }
+
+        pub fn with_tangents(mut self, tangent: Direction, bitangent: Direction) -> Self {
+            self.tangent = tangent;
+            self.bitangent = bitangent;
+            self
+        }
+    }
+
+    /// Any two directions perpendicular to `normal` and each other, for surfaces without UV
+    /// coordinates
+    pub fn tangent_frame(normal: Normal) -> (Direction, Direction) {
+        let helper = if normal.x.abs() > 0.9 {
+            Direction::new(0.0, 1.0, 0.0)
+        } else {
+            Direction::new(1.0, 0.0, 0.0)
+        };
+        let tangent = helper.cross(&normal).normalize();
+        let bitangent = normal.cross(&tangent).normalize();
+        (tangent, bitangent)
+    }
+}
+
+pub mod lights {
+    use std::sync::Arc;
+    pub mod directional_light;
+    pub use directional_light::*;
+    pub mod point_light;
+    pub use point_light::*;
+    pub mod spot_light;
+    pub use spot_light::*;
+
+    use crate::objects::Distance;
+    use crate::type_aliases::{Color, Direction, Point};
+
+    /// How the light fades with the distance to the light source
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum Falloff {
+        None,
+        Linear,
+        #[default]
+        Quadratic,
+    }
+
+    impl Falloff {
+        pub fn attenuation(&self, distance: Distance) -> f64 {
+            match self {
+                Falloff::None => 1.0,
+                Falloff::Linear => 1.0 / distance.max(1e-3),
+                Falloff::Quadratic => 1.0 / distance.powi(2).max(1e-6),
+            }
+        }
     }
+
+    /// Light arriving at a point from a light source
+    pub struct LightSample {
+        /// Normalized direction from the point towards the light
+        pub direction: Direction,
+        pub distance: Distance,
+        pub radiance: Color,
+    }
+
+    /// Light sources that are not part of the geometry. They are sampled with shadow rays from
+    /// diffusive surfaces.
+    pub trait LightSource: Send + Sync {
+        /// Returns `None` if the light does not reach `point`
+        fn illuminate(&self, point: Point) -> Option<LightSample>;
+    }
+
+    pub type Lights = Vec<Arc<dyn LightSource>>;
 }
 
 pub mod textures {
     use crate::type_aliases::Color;
-
-    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub mod medium;
+    pub use medium::*;
+    pub mod procedural;
+    pub use procedural::*;
+    pub mod image;
+    pub use image::*;
+    pub mod normal_map;
+    pub use normal_map::*;
+    mod pixbuf;
+
+    #[derive(Debug, Clone, PartialEq)]
     pub enum Texture {
-        Light(Color),
+        Light(Emission),
         Diffusive(Color),
+        /// Diffusive with a color that varies over the surface
+        Procedural(Procedural),
+        /// Diffusive with the color taken from an image
+        Image(ImageTexture),
         Reflective,
+        /// Scattering inside a participating medium, see `objects::Volume`
+        Volume(Medium),
+    }
+
+    /// `Emission` is the light given off by a `Texture::Light` surface.
+    ///
+    /// `strength` multiplies the color, so a lamp can be brighter than white. If `temperature`
+    /// is set, the color of a black body at that temperature in Kelvin is used instead of `color`.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Emission {
+        pub color: Color,
+        pub strength: f64,
+        pub temperature: Option<f64>,
+    }
+
+    impl Emission {
+        pub fn new(color: Color, strength: f64) -> Self {
+            Self {
+                color,
+                strength,
+                temperature: None,
+            }
+        }
+
+        pub fn blackbody(temperature: f64, strength: f64) -> Self {
+            Self {
+                color: blackbody_color(temperature),
+                strength,
+                temperature: Some(temperature),
+            }
+        }
+
+        /// The emitted light, in the same 0-255 scale as `Color` but not capped at 255
+        pub fn radiance(&self) -> Color {
+            let color = match self.temperature {
+                Some(temperature) => blackbody_color(temperature),
+                None => self.color,
+            };
+            color * self.strength
+        }
+    }
+
+    /// Approximate color of a black body at `temperature` Kelvin, valid from 1000K to 40000K.
+    ///
+    /// Uses the curve fit by [Tanner Helland](https://tannerhelland.com/2012/09/18/convert-temperature-rgb-algorithm-code.html).
+    pub fn blackbody_color(temperature: f64) -> Color {
+        let t = temperature.clamp(1000.0, 40000.0) / 100.0;
+
+        let red = if t <= 66.0 {
+            255.0
+        } else {
+            329.698727446 * (t - 60.0).powf(-0.1332047592)
+        };
+
+        let green = if t <= 66.0 {
+            99.4708025861 * t.ln() - 161.1195681661
+        } else {
+            288.1221695283 * (t - 60.0).powf(-0.0755148492)
+        };
+
+        let blue = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.5177312231 * (t - 10.0).ln() - 305.0447927307
+        };
+
+        Color::new(
+            red.clamp(0.0, 255.0),
+            green.clamp(0.0, 255.0),
+            blue.clamp(0.0, 255.0),
+        )
     }
 }
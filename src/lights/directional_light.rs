@@ -0,0 +1,31 @@
+use crate::lights::{LightSample, LightSource};
+use crate::type_aliases::{Color, Direction, Point};
+
+/// `DirectionalLight` is a light infinitely far away, like the sun. All rays are parallel to
+/// `direction` and the light does not fade with distance.
+#[derive(Debug, Clone)]
+pub struct DirectionalLight {
+    pub direction: Direction,
+    pub color: Color,
+    pub intensity: f64,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Direction, color: Color, intensity: f64) -> Self {
+        Self {
+            direction: direction.normalize(),
+            color,
+            intensity,
+        }
+    }
+}
+
+impl LightSource for DirectionalLight {
+    fn illuminate(&self, _point: Point) -> Option<LightSample> {
+        Some(LightSample {
+            direction: -self.direction,
+            distance: f64::INFINITY,
+            radiance: self.color * self.intensity,
+        })
+    }
+}
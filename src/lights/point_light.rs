@@ -0,0 +1,38 @@
+use crate::lights::{Falloff, LightSample, LightSource};
+use crate::type_aliases::{Color, Point};
+
+/// `PointLight` shines equally in all directions from a single point.
+#[derive(Debug, Clone)]
+pub struct PointLight {
+    pub position: Point,
+    pub color: Color,
+    pub intensity: f64,
+    pub falloff: Falloff,
+}
+
+impl PointLight {
+    pub fn new(position: Point, color: Color, intensity: f64, falloff: Falloff) -> Self {
+        Self {
+            position,
+            color,
+            intensity,
+            falloff,
+        }
+    }
+}
+
+impl LightSource for PointLight {
+    fn illuminate(&self, point: Point) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance = to_light.norm();
+        if distance <= 1e-6 {
+            return None;
+        }
+
+        Some(LightSample {
+            direction: to_light / distance,
+            distance,
+            radiance: self.color * self.intensity * self.falloff.attenuation(distance),
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use crate::lights::{Falloff, LightSample, LightSource};
+use crate::type_aliases::{Color, Direction, Point};
+
+/// `SpotLight` is a point light limited to a cone around `direction`.
+///
+/// Inside `inner_angle` the light has full strength, between `inner_angle` and `outer_angle` it
+/// fades out smoothly. Both angles are in degrees, measured from the center of the cone.
+#[derive(Debug, Clone)]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Direction,
+    pub color: Color,
+    pub intensity: f64,
+    pub falloff: Falloff,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Point,
+        direction: Direction,
+        color: Color,
+        intensity: f64,
+        falloff: Falloff,
+        inner_angle: f64,
+        outer_angle: f64,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.normalize(),
+            color,
+            intensity,
+            falloff,
+            inner_angle: inner_angle.min(outer_angle),
+            outer_angle,
+        }
+    }
+
+    /// How much of the light reaches `direction` (pointing away from the light), from 0.0 to 1.0
+    fn cone_factor(&self, direction: Direction) -> f64 {
+        let cos_angle = self.direction.dot(&direction);
+        let cos_inner = self.inner_angle.to_radians().cos();
+        let cos_outer = self.outer_angle.to_radians().cos();
+
+        if cos_angle >= cos_inner {
+            1.0
+        } else if cos_angle <= cos_outer {
+            0.0
+        } else {
+            let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t) // Smooth step
+        }
+    }
+}
+
+impl LightSource for SpotLight {
+    fn illuminate(&self, point: Point) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance = to_light.norm();
+        if distance <= 1e-6 {
+            return None;
+        }
+
+        let direction = to_light / distance;
+        let cone_factor = self.cone_factor(-direction);
+        if cone_factor <= 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.color
+                * self.intensity
+                * self.falloff.attenuation(distance)
+                * cone_factor,
+        })
+    }
+}
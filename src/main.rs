@@ -1,28 +1,179 @@
+use rt::config::camera::DEFAULT_CHECKPOINT_INTERVAL;
 use rt::gui::launch_gui;
-use rt::raytracer::{CameraBuilder, Scene};
+use rt::raytracer::{
+    Animation, CameraBuilder, CameraTracks, Convergence, FisheyeMapping, Interpolation, Projection,
+    Scene, SequenceOptions, Stereo, StereoLayout, Track,
+};
 use rt::type_aliases::Point;
 use std::env;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 const OUTPUT_PATH: &str = "output.ppm";
+const STEREO_OUTPUT_PATH: &str = "stereo.png";
+const DEFAULT_INTEROCULAR_DISTANCE: f64 = 0.065;
+/// Fewer samples than `config::camera::DEFAULT_SAMPLE_SIZE`, so headless renders finish quickly
+const CLI_SAMPLE_SIZE: u16 = 100;
+const FRAMES_DIRECTORY: &str = "frames";
+const FRAMES_PER_SECOND: f64 = 24.0;
 
+/// Headless options:
+///
+/// - `--samples <n>` samples per pixel. When resuming, only the missing samples are rendered.
+/// - `--checkpoint <path>` periodically save the render so it can be resumed.
+/// - `--resume <path>` continue a render from a checkpoint. Keeps checkpointing to the same
+///   file unless `--checkpoint` is given.
+/// - `--projection <name>` `perspective` (default), `orthographic`, `fisheye`,
+///   `fisheye-equisolid` or `equirectangular`.
+/// - `--region <x>,<y>,<width>,<height>` only render this rectangle of pixels, counted from the
+///   top left. The rest of the image stays black, or keeps what was resumed.
+/// - `--stereo <layout>` render both eyes to `stereo.png`, `side-by-side`, `over-under` or
+///   `separate` for `stereo_left.png` and `stereo_right.png`.
+/// - `--interocular <distance>` distance between the eyes, 0.065 by default.
+/// - `--toe-in` turn the eyes towards each other instead of shifting the images.
+/// - `--turntable <frames>` render the camera circling the scene to `frames/frame_0001.png` and
+///   onwards.
+/// - `--frames <first>-<last>` only render these frames of the turntable.
+/// - `--resume-frames` skip the frames that were already rendered and continue the interrupted
+///   one.
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.contains(&"no-gui".to_string()) {
-        let mut camera = CameraBuilder::new()
-            .sample_size(100)
+        let sample_size = match arg_value(&args, "--samples").map(|s| s.parse::<u16>()) {
+            Some(Ok(sample_size)) if sample_size > 0 => sample_size,
+            Some(_) => {
+                eprintln!("--samples must be a number between 1 and {}", u16::MAX);
+                std::process::exit(1);
+            }
+            None => CLI_SAMPLE_SIZE,
+        };
+        let resume_path = arg_value(&args, "--resume");
+        let checkpoint_path = arg_value(&args, "--checkpoint").or(resume_path);
+
+        let mut builder = CameraBuilder::new();
+        builder
+            .sample_size(sample_size)
             .position_by_coordinates(Point::new(-6.0, 6.0, 15.0))
             .look_at(Point::new(0.0, 0.0, 0.0))
             .focal_length(2.0)
-            .resolution(800, 600)
-            .build();
+            .resolution(800, 600);
+
+        if let Some(name) = arg_value(&args, "--projection") {
+            let Some(projection) = parse_projection(name) else {
+                eprintln!("Unknown projection {name}");
+                std::process::exit(1);
+            };
+            if projection == Projection::Equirectangular {
+                builder.resolution(1200, 600);
+            }
+            builder.projection(projection);
+        }
+
+        if let Some(region) = arg_value(&args, "--region") {
+            let Some([x, y, width, height]) = parse_region(region) else {
+                eprintln!("--region must be four numbers like 100,50,200,150");
+                std::process::exit(1);
+            };
+            builder.region(x, y, width, height);
+        }
+
+        if let Some(layout) = arg_value(&args, "--stereo") {
+            let layout = match layout {
+                "side-by-side" => StereoLayout::SideBySide,
+                "over-under" => StereoLayout::OverUnder,
+                "separate" => StereoLayout::Separate,
+                _ => {
+                    eprintln!("Unknown stereo layout {layout}");
+                    std::process::exit(1);
+                }
+            };
+            let interocular_distance = match arg_value(&args, "--interocular").map(str::parse) {
+                Some(Ok(distance)) => distance,
+                Some(Err(_)) => {
+                    eprintln!("--interocular must be a number");
+                    std::process::exit(1);
+                }
+                None => DEFAULT_INTEROCULAR_DISTANCE,
+            };
+            let convergence = if args.contains(&"--toe-in".to_string()) {
+                Convergence::ToeIn
+            } else {
+                Convergence::OffAxis
+            };
+
+            let stereo = Stereo::new(interocular_distance)
+                .with_convergence(convergence)
+                .with_layout(layout);
+            let start = Instant::now();
+            if let Err(err) =
+                stereo.render(&builder, Arc::new(Scene::init(0.5)), STEREO_OUTPUT_PATH)
+            {
+                eprintln!("Failed to write {STEREO_OUTPUT_PATH}: {err}");
+                std::process::exit(1);
+            }
+            println!("Time taken for rendering: {:?}", start.elapsed());
+            return;
+        }
+
+        if let Some(frames) = arg_value(&args, "--turntable") {
+            let Ok(frame_count) = frames.parse::<u32>() else {
+                eprintln!("--turntable must be a number of frames");
+                std::process::exit(1);
+            };
+            let frames = match arg_value(&args, "--frames")
+                .map(|range| parse_frame_range(range, frame_count))
+            {
+                Some(Some(frames)) => frames,
+                Some(None) => {
+                    eprintln!("--frames must be a range of frames like 1-24, up to {frame_count}");
+                    std::process::exit(1);
+                }
+                None => 1..=frame_count,
+            };
+
+            let scene = Scene::init(0.5);
+            let animation = Animation::new(FRAMES_PER_SECOND)
+                .with_camera(turntable(Point::new(-6.0, 6.0, 15.0), frame_count));
+            let options = SequenceOptions {
+                directory: FRAMES_DIRECTORY.to_string(),
+                frames,
+                resume: args.contains(&"--resume-frames".to_string()),
+            };
+
+            let start = Instant::now();
+            animation.render(&builder, &scene, &options);
+            println!("Time taken for rendering: {:?}", start.elapsed());
+            return;
+        }
+
+        if let Some(path) = checkpoint_path {
+            builder.checkpoint(path, DEFAULT_CHECKPOINT_INTERVAL);
+        }
+
+        let mut camera = builder.build();
+
+        if let Some(path) = resume_path {
+            if let Err(err) = camera.resume_from_checkpoint(path) {
+                eprintln!("Failed to resume from {path}: {err}");
+                std::process::exit(1);
+            }
+            println!("Resuming render from {path}");
+        }
 
         let scene = Arc::new(Scene::init(0.5));
 
         let start = Instant::now();
 
-        // Perform ray tracing
-        camera.send_rays(scene.clone());
+        // Perform ray tracing, reporting progress for every finished tile
+        let total_tiles = camera.tiles().len();
+        let finished_tiles = AtomicUsize::new(0);
+        camera.send_rays_with_callback(scene.clone(), |_, _| {
+            let finished = finished_tiles.fetch_add(1, Ordering::Relaxed) + 1;
+            print!("\rRendered tiles: {finished}/{total_tiles}");
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+        });
+        println!();
         camera.write_to_ppm(OUTPUT_PATH);
 
         let duration = start.elapsed();
@@ -31,3 +182,90 @@ fn main() {
         launch_gui();
     }
 }
+
+/// The value following `flag` in the arguments, e.g. `--resume render.ckpt`
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
+}
+
+fn parse_projection(name: &str) -> Option<Projection> {
+    let fisheye = |mapping| Projection::Fisheye {
+        mapping,
+        field_of_view: 180.0,
+    };
+    match name {
+        "perspective" => Some(Projection::Perspective),
+        "orthographic" => Some(Projection::Orthographic { height: 10.0 }),
+        "fisheye" => Some(fisheye(FisheyeMapping::Equidistant)),
+        "fisheye-equisolid" => Some(fisheye(FisheyeMapping::Equisolid)),
+        "equirectangular" => Some(Projection::Equirectangular),
+        _ => None,
+    }
+}
+
+/// Parse a region like `100,50,200,150`
+fn parse_region(region: &str) -> Option<[u32; 4]> {
+    let numbers: Vec<u32> = region
+        .split(',')
+        .map(|number| number.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    numbers.try_into().ok()
+}
+
+/// Parse a frame range like `1-24` within the `frame_count` frames of an animation
+fn parse_frame_range(range: &str, frame_count: u32) -> Option<RangeInclusive<u32>> {
+    let (first, last) = range.split_once('-')?;
+    let (first, last) = (first.parse().ok()?, last.parse().ok()?);
+    ((1..=last).contains(&first) && last <= frame_count).then_some(first..=last)
+}
+
+/// The camera going once around the Y-axis in `frames` frames, starting at `position`
+fn turntable(position: Point, frames: u32) -> CameraTracks {
+    let duration = frames as f64 / FRAMES_PER_SECOND;
+    let mut track = Track::new(Interpolation::CatmullRom);
+    // Enough keyframes for the curve between them to stay close to a circle
+    for step in 0..=16 {
+        let angle = step as f64 / 16.0 * std::f64::consts::TAU;
+        let (sin, cos) = angle.sin_cos();
+        let keyframe = Point::new(
+            position.x * cos + position.z * sin,
+            position.y,
+            position.z * cos - position.x * sin,
+        );
+        track = track.with_key(duration * step as f64 / 16.0, keyframe);
+    }
+
+    CameraTracks {
+        position: track,
+        ..CameraTracks::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_ranges() {
+        assert_eq!(parse_frame_range("1-24", 24), Some(1..=24));
+        assert_eq!(parse_frame_range("5-5", 24), Some(5..=5));
+        assert_eq!(parse_frame_range("0-5", 24), None);
+        assert_eq!(parse_frame_range("6-5", 24), None);
+        assert_eq!(parse_frame_range("1-25", 24), None);
+        assert_eq!(parse_frame_range("1", 24), None);
+        assert_eq!(parse_frame_range("a-b", 24), None);
+    }
+
+    #[test]
+    fn regions() {
+        assert_eq!(parse_region("100,50,200,150"), Some([100, 50, 200, 150]));
+        assert_eq!(parse_region(" 1, 2 ,3,4 "), Some([1, 2, 3, 4]));
+        assert_eq!(parse_region("1,2,3"), None);
+        assert_eq!(parse_region("1,2,3,4,5"), None);
+        assert_eq!(parse_region("1,2,-3,4"), None);
+        assert_eq!(parse_region(""), None);
+    }
+}
@@ -0,0 +1,126 @@
+use super::Texture;
+use crate::objects::{discriminant, tangent_frame, Intersection, Object};
+use crate::raytracer::Ray;
+use crate::type_aliases::{Direction, Normal, Point, Uv};
+use std::f64::consts::PI;
+
+/// `Capsule` is a cylinder along the Y-axis with a half sphere on each end. `center` is the
+/// center of the bottom half sphere and `height` the distance to the top one.
+#[derive(Debug, Clone)]
+pub struct Capsule {
+    pub center: Point,
+    pub radius: f64,
+    pub height: f64,
+    pub texture: Texture,
+}
+
+impl Capsule {
+    pub fn new(center: Point, radius: f64, height: f64, texture: Texture) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            texture,
+        }
+    }
+
+    /// Points away from the closest point on the line between the two half spheres
+    fn normal(&self, point: Point) -> Normal {
+        let local = point - self.center;
+        let closest = Point::new(0.0, local.y.clamp(0.0, self.height), 0.0);
+        (local - closest).normalize()
+    }
+
+    /// Cylindrical coordinates, with `v` going from the bottom to the top of the whole capsule
+    fn uv(&self, point: Point) -> Uv {
+        let local = point - self.center;
+        Uv::new(
+            0.5 + local.z.atan2(local.x) / (2.0 * PI),
+            (local.y + self.radius) / (self.height + 2.0 * self.radius),
+        )
+    }
+
+    /// Directions in which `u` and `v` grow. At the poles `u` is undefined, so any direction
+    /// along the surface is used.
+    fn tangents(&self, point: Point, normal: Normal) -> (Direction, Direction) {
+        let local = point - self.center;
+        match Direction::new(-local.z, 0.0, local.x).try_normalize(1e-9) {
+            Some(tangent) => (tangent, tangent.cross(&normal)),
+            None => tangent_frame(normal),
+        }
+    }
+
+    /// Distances to the infinite cylinder around the Y-axis
+    fn side_distances(&self, origin: Point, direction: Point) -> Vec<f64> {
+        let a = direction.x.powi(2) + direction.z.powi(2);
+        let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+        let c = origin.x.powi(2) + origin.z.powi(2) - self.radius.powi(2);
+        quadratic_roots(a, b, c)
+    }
+
+    /// Distances to a sphere with the capsule's radius at `center`
+    fn sphere_distances(&self, origin: Point, direction: Point, center: Point) -> Vec<f64> {
+        let offset = origin - center;
+        let b = 2.0 * offset.dot(&direction);
+        let c = offset.dot(&offset) - self.radius.powi(2);
+        quadratic_roots(direction.dot(&direction), b, c)
+    }
+}
+
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return vec![];
+    }
+    match discriminant(a, b, c) {
+        Some(discriminant) => {
+            let sqrt_discriminant = discriminant.sqrt();
+            vec![
+                (-b - sqrt_discriminant) / (2.0 * a),
+                (-b + sqrt_discriminant) / (2.0 * a),
+            ]
+        }
+        None => vec![],
+    }
+}
+
+impl Object for Capsule {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let origin = ray.origin - self.center;
+        let direction = ray.direction;
+        let height_at = |dist: f64| origin.y + direction.y * dist;
+
+        // Each surface only counts on its own part of the capsule
+        let side = self
+            .side_distances(origin, direction)
+            .into_iter()
+            .filter(|&dist| (0.0..=self.height).contains(&height_at(dist)));
+        let bottom = self
+            .sphere_distances(origin, direction, Point::zeros())
+            .into_iter()
+            .filter(|&dist| height_at(dist) < 0.0);
+        let top = self
+            .sphere_distances(origin, direction, Point::new(0.0, self.height, 0.0))
+            .into_iter()
+            .filter(|&dist| height_at(dist) > self.height);
+
+        let dist = side
+            .chain(bottom)
+            .chain(top)
+            .filter(|dist| (1e-6..ray.intersection_dist).contains(dist))
+            .min_by(|a, b| a.total_cmp(b))?;
+
+        let hit_point = ray.origin + direction * dist;
+        let normal = self.normal(hit_point);
+        let (tangent, bitangent) = self.tangents(hit_point, normal);
+        Some(
+            Intersection::new(hit_point, normal, dist, self.texture())
+                .with_local_point(hit_point - self.center)
+                .with_uv(self.uv(hit_point))
+                .with_tangents(tangent, bitangent),
+        )
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture.clone()
+    }
+}
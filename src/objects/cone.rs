@@ -0,0 +1,150 @@
+use super::Texture;
+use crate::objects::{tangent_frame, FlatPlane, Intersection, Object};
+use crate::raytracer::Ray;
+use crate::type_aliases::{Direction, Directions, Normal, Point, Uv};
+use std::f64::consts::PI;
+
+/// `Cone` stands on its base at `center` and narrows along the Y-axis to `top_radius` at
+/// `height`. A `top_radius` of 0.0 gives a pointed cone, anything else a truncated one.
+#[derive(Debug)]
+pub struct Cone {
+    pub center: Point,
+    pub radius: f64,
+    pub top_radius: f64,
+    pub height: f64,
+    pub bottom: FlatPlane,
+    pub top: Option<FlatPlane>,
+    pub texture: Texture,
+}
+
+impl Cone {
+    pub fn new(center: Point, radius: f64, top_radius: f64, height: f64, texture: Texture) -> Self {
+        let bottom = FlatPlane::new(center, radius, texture.clone());
+        let top = (top_radius > 0.0).then(|| {
+            FlatPlane::new(
+                Point::new(center.x, center.y + height, center.z),
+                top_radius,
+                texture.clone(),
+            )
+        });
+        Self {
+            center,
+            radius,
+            top_radius,
+            height,
+            bottom,
+            top,
+            texture,
+        }
+    }
+
+    /// How much the radius changes per unit of height
+    fn slope(&self) -> f64 {
+        (self.top_radius - self.radius) / self.height
+    }
+
+    /// Normal of the slanted side
+    fn normal(&self, point: Point) -> Normal {
+        let local = point - self.center;
+        let around = Normal::new(local.x, 0.0, local.z);
+        match around.try_normalize(1e-9) {
+            Some(around) => Normal::new(around.x, -self.slope(), around.z).normalize(),
+            None => Normal::up(), // The tip of the cone
+        }
+    }
+
+    /// Cylindrical coordinates on the side, like `Cylinder`
+    fn uv(&self, point: Point) -> Uv {
+        let local = point - self.center;
+        Uv::new(
+            0.5 + local.z.atan2(local.x) / (2.0 * PI),
+            local.y / self.height,
+        )
+    }
+
+    /// Directions in which `u` and `v` grow on the side. At the tip `u` is undefined, so any
+    /// direction along the surface is used.
+    fn tangents(&self, point: Point, normal: Normal) -> (Direction, Direction) {
+        let local = point - self.center;
+        match Direction::new(-local.z, 0.0, local.x).try_normalize(1e-9) {
+            Some(tangent) => (tangent, tangent.cross(&normal)),
+            None => tangent_frame(normal),
+        }
+    }
+}
+
+impl Object for Cone {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let origin = ray.origin - self.center;
+        let direction = ray.direction;
+        let slope = self.slope();
+        let origin_radius = self.radius + slope * origin.y;
+
+        // x² + z² = (radius + slope * y)² along the ray
+        let a = direction.x.powi(2) + direction.z.powi(2) - (slope * direction.y).powi(2);
+        let b = 2.0
+            * (origin.x * direction.x + origin.z * direction.z
+                - origin_radius * slope * direction.y);
+        let c = origin.x.powi(2) + origin.z.powi(2) - origin_radius.powi(2);
+
+        let distances = if a.abs() < 1e-12 {
+            // The ray is parallel to the side
+            vec![-c / b]
+        } else {
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant < 0.0 {
+                vec![]
+            } else {
+                let sqrt_discriminant = discriminant.sqrt();
+                vec![
+                    (-b - sqrt_discriminant) / (2.0 * a),
+                    (-b + sqrt_discriminant) / (2.0 * a),
+                ]
+            }
+        };
+
+        let mut valid_intersections = Vec::new();
+        for dist in distances {
+            if !(1e-6..ray.intersection_dist).contains(&dist) {
+                continue;
+            }
+
+            // Skip the mirrored cone above the tip
+            let height = origin.y + direction.y * dist;
+            if (0.0..=self.height).contains(&height) && self.radius + slope * height >= 0.0 {
+                let point = ray.origin + direction * dist;
+                let normal = self.normal(point);
+                let (tangent, bitangent) = self.tangents(point, normal);
+                valid_intersections.push(
+                    Intersection::new(point, normal, dist, self.texture())
+                        .with_local_point(point - self.center)
+                        .with_uv(self.uv(point))
+                        .with_tangents(tangent, bitangent),
+                );
+            }
+        }
+
+        // The caps always face out of the cone, so the inside can be told apart from the outside
+        for (cap, normal) in [
+            (Some(&self.bottom), Normal::down()),
+            (self.top.as_ref(), Normal::up()),
+        ] {
+            let Some(cap) = cap else {
+                continue;
+            };
+            if let Some(cap_intersection) = cap.intersection(ray) {
+                let mut cap_intersection = cap.hit(ray, cap_intersection.distance, normal);
+                cap_intersection.local_point += cap.center - self.center;
+                valid_intersections.push(cap_intersection);
+            }
+        }
+
+        valid_intersections
+            .into_iter()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture.clone()
+    }
+}
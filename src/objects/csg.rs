@@ -0,0 +1,267 @@
+use crate::objects::{Intersection, Object, Span};
+use crate::raytracer::Ray;
+use crate::textures::Texture;
+use std::sync::Arc;
+
+/// How many surfaces `probe_spans` follows a single ray through
+const MAX_PROBES: usize = 16;
+
+/// How two objects are combined by `Csg`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsgOperation {
+    /// Inside either object
+    Union,
+    /// Inside both objects, e.g. a lens from two spheres
+    Intersection,
+    /// Inside the left object but not the right one, e.g. a cube with a spherical hole
+    Difference,
+}
+
+/// `Csg` is a constructive solid geometry node combining two objects into one.
+///
+/// Both objects should be closed, with normals pointing out of them. The surfaces keep the
+/// textures of the objects they come from, and `Csg` nodes can be combined again.
+pub struct Csg {
+    pub operation: CsgOperation,
+    pub left: Arc<dyn Object>,
+    pub right: Arc<dyn Object>,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Arc<dyn Object>, right: Arc<dyn Object>) -> Self {
+        Self {
+            operation,
+            left,
+            right,
+        }
+    }
+
+    pub fn union(left: Arc<dyn Object>, right: Arc<dyn Object>) -> Self {
+        Self::new(CsgOperation::Union, left, right)
+    }
+
+    pub fn intersection(left: Arc<dyn Object>, right: Arc<dyn Object>) -> Self {
+        Self::new(CsgOperation::Intersection, left, right)
+    }
+
+    pub fn difference(left: Arc<dyn Object>, right: Arc<dyn Object>) -> Self {
+        Self::new(CsgOperation::Difference, left, right)
+    }
+
+    fn contains(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self.operation {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+impl Object for Csg {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        self.spans(ray)
+            .into_iter()
+            .flat_map(|span| [span.enter, span.exit])
+            .find(|hit| (1e-6..ray.intersection_dist).contains(&hit.distance))
+    }
+
+    fn texture(&self) -> Texture {
+        self.left.texture()
+    }
+
+    /// Walk through the surfaces of both objects in order, keeping the ones where the ray goes
+    /// in or out of the combined object
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let mut boundaries = Vec::new();
+        for (object, is_left) in [(&self.left, true), (&self.right, false)] {
+            for span in object.spans(ray) {
+                boundaries.push((span.enter, is_left, true));
+                boundaries.push((span.exit, is_left, false));
+            }
+        }
+        // Stable, so a span without thickness still enters before it exits
+        boundaries.sort_by(|a, b| a.0.distance.total_cmp(&b.0.distance));
+
+        let (mut inside_left, mut inside_right) = (false, false);
+        let mut spans = Vec::new();
+        let mut enter = None;
+
+        for (mut hit, is_left, entering) in boundaries {
+            let was_inside = self.contains(inside_left, inside_right);
+            if is_left {
+                inside_left = entering;
+            } else {
+                inside_right = entering;
+            }
+            let inside = self.contains(inside_left, inside_right);
+            if inside == was_inside {
+                continue;
+            }
+
+            // The surface of the hole cut by a difference faces into the hole
+            if self.operation == CsgOperation::Difference && !is_left {
+                hit.normal = -hit.normal;
+            }
+
+            if inside {
+                enter = Some(hit);
+            } else if let Some(enter) = enter.take() {
+                spans.push(Span { enter, exit: hit });
+            }
+        }
+
+        spans
+    }
+}
+
+/// Find the spans of any object by following the ray from one hit to the next. A hit with the
+/// normal facing the ray enters the object, anything else leaves it.
+pub fn probe_spans<O: Object + ?Sized>(object: &O, ray: &Ray) -> Vec<Span> {
+    let offset = 1e-4;
+    let mut spans = Vec::new();
+    let mut enter: Option<Intersection> = None;
+    let mut travelled = 0.0;
+
+    for _ in 0..MAX_PROBES {
+        let origin = ray.origin + ray.direction * travelled;
+        let mut probe = Ray::new(origin, ray.direction, ray.depth).with_time(ray.time);
+        probe.sample = ray.sample;
+        let Some(mut hit) = object.intersection(&probe) else {
+            break;
+        };
+        hit.distance += travelled;
+        travelled = hit.distance + offset;
+
+        if hit.normal.dot(&ray.direction) < 0.0 {
+            // Two entries in a row come from a surface without an inside, like a `FlatPlane`
+            if let Some(previous) = enter.replace(hit) {
+                spans.push(Span {
+                    enter: previous.clone(),
+                    exit: previous,
+                });
+            }
+        } else {
+            // Leaving without entering first means the ray started inside
+            let enter = enter.take().unwrap_or_else(|| {
+                Intersection::new(ray.origin, -ray.direction, 0.0, hit.texture.clone())
+            });
+            spans.push(Span { enter, exit: hit });
+        }
+    }
+
+    if let Some(last) = enter {
+        spans.push(Span {
+            enter: last.clone(),
+            exit: last,
+        });
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{Cube, Cylinder, FlatPlane, Sphere};
+    use crate::type_aliases::Point;
+
+    fn distances(spans: &[Span]) -> Vec<(f64, f64)> {
+        spans
+            .iter()
+            .map(|span| (span.enter.distance, span.exit.distance))
+            .collect()
+    }
+
+    fn assert_spans(spans: &[Span], expected: &[(f64, f64)]) {
+        let spans = distances(spans);
+        assert_eq!(spans.len(), expected.len(), "{spans:?}");
+        for (span, expected) in spans.iter().zip(expected) {
+            assert!(
+                (span.0 - expected.0).abs() < 1e-3 && (span.1 - expected.1).abs() < 1e-3,
+                "{spans:?} != {expected:?}"
+            );
+        }
+    }
+
+    fn ray(origin: Point, direction: Point) -> Ray {
+        Ray::new(origin, direction, 0)
+    }
+
+    #[test]
+    fn probes_through_a_sphere() {
+        let sphere = Sphere::new(Point::zeros(), 1.0, Texture::Reflective);
+        let ray = ray(Point::new(0.0, 0.0, 5.0), Point::new(0.0, 0.0, -1.0));
+        assert_spans(&probe_spans(&sphere, &ray), &[(4.0, 6.0)]);
+        assert_spans(&sphere.spans(&ray), &[(4.0, 6.0)]);
+    }
+
+    #[test]
+    fn probes_from_inside() {
+        let sphere = Sphere::new(Point::zeros(), 1.0, Texture::Reflective);
+        let ray = ray(Point::zeros(), Point::new(1.0, 0.0, 0.0));
+        let spans = probe_spans(&sphere, &ray);
+        assert_spans(&spans, &[(0.0, 1.0)]);
+        assert_eq!(spans[0].enter.hit_point, Point::zeros());
+    }
+
+    #[test]
+    fn surfaces_without_inside_give_empty_spans() {
+        let plane = FlatPlane::new(Point::zeros(), 10.0, Texture::Reflective);
+        let ray = ray(Point::new(0.0, 5.0, 0.0), Point::new(0.0, -1.0, 0.0));
+        assert_spans(&probe_spans(&plane, &ray), &[(5.0, 5.0)]);
+    }
+
+    #[test]
+    fn cylinder_spans_match_probing() {
+        let cylinder = Cylinder::new(Point::zeros(), 1.0, 2.0, Texture::Reflective);
+        for (origin, direction) in [
+            // Through both caps
+            (Point::new(0.0, 5.0, 0.0), Point::new(0.0, -1.0, 0.0)),
+            // Through a cap and the side
+            (Point::new(0.0, 5.0, 0.0), Point::new(0.3, -1.0, 0.0)),
+            // Through the side twice
+            (Point::new(-5.0, 1.0, 0.0), Point::new(1.0, 0.0, 0.0)),
+        ] {
+            let ray = ray(origin, direction);
+            let expected = distances(&probe_spans(&cylinder, &ray));
+            assert_spans(&cylinder.spans(&ray), &expected);
+            for span in cylinder.spans(&ray) {
+                assert!(span.enter.normal.dot(&ray.direction) < 0.0);
+                assert!(span.exit.normal.dot(&ray.direction) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn difference_cuts_a_hole() {
+        let cube = Arc::new(Cube::new(Point::zeros(), 2.0, Texture::Reflective));
+        let hole = Arc::new(Sphere::new(Point::zeros(), 0.5, Texture::Reflective));
+        let csg = Csg::difference(cube, hole);
+        let ray = ray(Point::new(0.0, 0.0, 5.0), Point::new(0.0, 0.0, -1.0));
+
+        assert_spans(&csg.spans(&ray), &[(4.0, 4.5), (5.5, 6.0)]);
+        // The wall of the hole faces into the hole, so the ray leaves the cube through it
+        let spans = csg.spans(&ray);
+        assert!(spans[0].exit.normal.dot(&ray.direction) > 0.0);
+        assert!(spans[1].enter.normal.dot(&ray.direction) < 0.0);
+        assert_eq!(csg.intersection(&ray).unwrap().distance, 4.0);
+    }
+
+    #[test]
+    fn intersection_keeps_the_overlap() {
+        let left = Arc::new(Sphere::new(
+            Point::new(-0.5, 0.0, 0.0),
+            1.0,
+            Texture::Reflective,
+        ));
+        let right = Arc::new(Sphere::new(
+            Point::new(0.5, 0.0, 0.0),
+            1.0,
+            Texture::Reflective,
+        ));
+        let lens = Csg::intersection(left, right);
+        let ray = ray(Point::new(-5.0, 0.0, 0.0), Point::new(1.0, 0.0, 0.0));
+
+        assert_spans(&lens.spans(&ray), &[(4.5, 5.5)]);
+    }
+}
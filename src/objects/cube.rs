@@ -1,6 +1,6 @@
-use crate::objects::{Intersection, Object, Texture};
+use crate::objects::{Intersection, Object, Span, Texture};
 use crate::raytracer::Ray;
-use crate::type_aliases::{Normal, Point};
+use crate::type_aliases::{Direction, Directions, Normal, Point, Uv};
 
 #[derive(Debug)]
 pub struct Cube {
@@ -32,10 +32,37 @@ impl Cube {
         normal[max] = local_point[max].signum();
         normal
     }
-}
 
-impl Object for Cube {
-    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+    /// Every face is mapped on its own, as seen from outside the cube with the Y-axis up. The
+    /// top and bottom faces have the Z-axis pointing down in the image.
+    fn uv(&self, point: Point, normal: Normal) -> Uv {
+        let local = (point - self.center) / self.size;
+        let (u, v) = if normal.x != 0.0 {
+            (-local.z * normal.x, local.y)
+        } else if normal.y != 0.0 {
+            (local.x, -local.z * normal.y)
+        } else {
+            (local.x * normal.z, local.y)
+        };
+        Uv::new(u + 0.5, v + 0.5)
+    }
+
+    /// Directions in which `u` and `v` grow on the face with `normal`
+    fn tangents(&self, normal: Normal) -> (Direction, Direction) {
+        if normal.x != 0.0 {
+            (Direction::new(0.0, 0.0, -normal.x), Direction::up())
+        } else if normal.y != 0.0 {
+            (
+                Direction::new(1.0, 0.0, 0.0),
+                Direction::new(0.0, 0.0, -normal.y),
+            )
+        } else {
+            (Direction::new(normal.z, 0.0, 0.0), Direction::up())
+        }
+    }
+
+    /// Distances along the ray to where it enters and leaves the cube
+    fn slab(&self, ray: &Ray) -> Option<(f64, f64)> {
         // Calculate the half size of the cube to determine its bounds.
         let half_size = self.size / 2.0;
         // Determine the minimum bounds of the cube in 3D space.
@@ -76,24 +103,51 @@ impl Object for Cube {
         if t_min > t_max || t_max < 0.0 {
             return None;
         }
+        Some((t_min, t_max))
+    }
 
-        // Determine the distance to the intersection point.
-        let distance = if t_min >= 0.0 { t_min } else { t_max };
+    fn hit(&self, ray: &Ray, distance: f64) -> Intersection {
         // Calculate the exact hit point on the cube's surface.
         let hit_point = ray.origin + distance * ray.direction * 1.00001;
         // Calculate the normal at the hit point.
         let normal = self.normal(hit_point);
 
+        let (tangent, bitangent) = self.tangents(normal);
+
         // Return the intersection data, including hit point, normal, distance, and texture.
-        Some(Intersection::new(
-            hit_point,
-            normal,
-            distance,
-            self.texture(),
-        ))
+        Intersection::new(hit_point, normal, distance, self.texture())
+            .with_local_point(hit_point - self.center)
+            .with_uv(self.uv(hit_point, normal))
+            .with_tangents(tangent, bitangent)
+    }
+}
+
+impl Object for Cube {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let (t_min, t_max) = self.slab(ray)?;
+        // Determine the distance to the intersection point.
+        let distance = if t_min >= 0.0 { t_min } else { t_max };
+        Some(self.hit(ray, distance))
+    }
+
+    /// The slab test gives both ends at once, so no probing is needed. A ray starting inside
+    /// enters where it starts.
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let Some((t_min, t_max)) = self.slab(ray) else {
+            return Vec::new();
+        };
+        let enter = if t_min >= 0.0 {
+            self.hit(ray, t_min)
+        } else {
+            Intersection::new(ray.origin, -ray.direction, 0.0, self.texture())
+        };
+        vec![Span {
+            enter,
+            exit: self.hit(ray, t_max),
+        }]
     }
 
     fn texture(&self) -> Texture {
-        self.texture
+        self.texture.clone()
     }
 }
@@ -1,7 +1,8 @@
 use super::Texture;
-use crate::objects::{discriminant, FlatPlane, Intersection, Object};
+use crate::objects::{discriminant, FlatPlane, Intersection, Object, Span};
 use crate::raytracer::Ray;
-use crate::type_aliases::{Direction, Directions, Normal, Point};
+use crate::type_aliases::{Direction, Directions, Normal, Point, Uv};
+use std::f64::consts::PI;
 
 #[derive(Debug)]
 pub struct Cylinder {
@@ -15,11 +16,11 @@ pub struct Cylinder {
 
 impl Cylinder {
     pub fn new(center: Point, radius: f64, height: f64, texture: Texture) -> Self {
-        let bottom = FlatPlane::new(center, radius, texture);
+        let bottom = FlatPlane::new(center, radius, texture.clone());
         let top = FlatPlane::new(
             Point::new(center.x, center.y + height, center.z),
             radius,
-            texture,
+            texture.clone(),
         );
         Self {
             center,
@@ -44,6 +45,81 @@ impl Cylinder {
             (point - self.center - projection).normalize()
         }
     }
+
+    /// Cylindrical coordinates on the side, with `u` going around the Y-axis and `v` from the
+    /// bottom to the top. The caps use the planar mapping of `FlatPlane`.
+    fn uv(&self, point: Point) -> Uv {
+        let local = point - self.center;
+        Uv::new(
+            0.5 + local.z.atan2(local.x) / (2.0 * PI),
+            local.y / self.height,
+        )
+    }
+
+    /// Directions in which `u` and `v` grow on the side
+    fn tangents(&self, normal: Normal) -> (Direction, Direction) {
+        (Direction::new(-normal.z, 0.0, normal.x), Direction::up())
+    }
+
+    fn side_hit(&self, ray: &Ray, dist: f64) -> Intersection {
+        let point = ray.origin + ray.direction * dist;
+        // Add a small offset depending on texture
+        let offset = if matches!(self.texture, Texture::Reflective) {
+            1.0 + 1e-7
+        } else {
+            1.0
+        };
+
+        let normal = self.normal(point);
+        let (tangent, bitangent) = self.tangents(normal);
+        Intersection::new(point * offset, normal, dist, self.texture())
+            .with_local_point(point - self.center)
+            .with_uv(self.uv(point))
+            .with_tangents(tangent, bitangent)
+    }
+
+    /// A hit on one of the caps, with the normal pointing out of the cylinder even when the ray
+    /// comes from inside, relative to the cylinder instead of the cap
+    fn cap_hit(&self, cap: &FlatPlane, ray: &Ray, dist: f64) -> Intersection {
+        let normal = if cap.center.y > self.center.y {
+            Normal::up()
+        } else {
+            Normal::down()
+        };
+        let mut cap_intersection = cap.hit(ray, dist, normal);
+        cap_intersection.local_point += cap.center - self.center;
+        cap_intersection
+    }
+
+    /// Distances along the ray between the planes of the caps, the closest first
+    fn cap_distances(&self, ray: &Ray) -> Option<(f64, f64)> {
+        let (bottom, top) = (self.bottom.center.y, self.top.center.y);
+        if ray.direction.y.abs() < 1e-12 {
+            return (bottom..=top)
+                .contains(&ray.origin.y)
+                .then_some((f64::NEG_INFINITY, f64::INFINITY));
+        }
+        let dist_1 = (bottom - ray.origin.y) / ray.direction.y;
+        let dist_2 = (top - ray.origin.y) / ray.direction.y;
+        Some((dist_1.min(dist_2), dist_1.max(dist_2)))
+    }
+
+    /// Distances along the ray inside the endless tube around the Y-axis, the closest first
+    fn tube_distances(&self, ray: &Ray) -> Option<(f64, f64)> {
+        let origin = ray.origin - self.center;
+        let direction = ray.direction;
+        let a = direction.x.powi(2) + direction.z.powi(2);
+        let c = origin.x.powi(2) + origin.z.powi(2) - self.radius.powi(2);
+        if a < 1e-12 {
+            return (c <= 0.0).then_some((f64::NEG_INFINITY, f64::INFINITY));
+        }
+        let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+        let sqrt_discriminant = discriminant(a, b, c)?.sqrt();
+        Some((
+            (-b - sqrt_discriminant) / (2.0 * a),
+            (-b + sqrt_discriminant) / (2.0 * a),
+        ))
+    }
 }
 
 impl Object for Cylinder {
@@ -77,29 +153,16 @@ impl Object for Cylinder {
                 let height = (point - bottom).dot(&axis);
 
                 if (0.0..=self.height).contains(&height) && dist < ray.intersection_dist {
-                    // Add a small offset depending on texture
-                    let offset = if matches!(self.texture, Texture::Reflective) {
-                        1.0 + 1e-7
-                    } else {
-                        1.0
-                    };
-
-                    valid_intersections.push(Intersection::new(
-                        point * offset,
-                        self.normal(point),
-                        dist,
-                        self.texture,
-                    ));
+                    valid_intersections.push(self.side_hit(ray, dist));
                 }
             }
         }
 
         // Check intersections with both caps
-        if let Some(bottom_intersection) = self.bottom.intersection(ray) {
-            valid_intersections.push(bottom_intersection);
-        }
-        if let Some(top_intersection) = self.top.intersection(ray) {
-            valid_intersections.push(top_intersection);
+        for cap in [&self.bottom, &self.top] {
+            if let Some(cap_intersection) = cap.intersection(ray) {
+                valid_intersections.push(self.cap_hit(cap, ray, cap_intersection.distance));
+            }
         }
 
         // Find the closest valid intersection. If no intersection was found. Return None.
@@ -109,6 +172,39 @@ impl Object for Cylinder {
     }
 
     fn texture(&self) -> Texture {
-        self.texture
+        self.texture.clone()
+    }
+
+    /// The ray is inside where it is both between the caps and inside the tube. The caps are
+    /// flat, so their normals can not tell entering from leaving.
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let (Some(caps), Some(tube)) = (self.cap_distances(ray), self.tube_distances(ray)) else {
+            return Vec::new();
+        };
+        let enter = caps.0.max(tube.0);
+        let exit = caps.1.min(tube.1);
+        if enter >= exit || exit <= 1e-6 {
+            return Vec::new();
+        }
+
+        // The cap the ray comes in through is the one it reaches first
+        let (first_cap, last_cap) = if ray.direction.y > 0.0 {
+            (&self.bottom, &self.top)
+        } else {
+            (&self.top, &self.bottom)
+        };
+        let enter = enter.max(0.0);
+        vec![Span {
+            enter: if caps.0 > tube.0 {
+                self.cap_hit(first_cap, ray, enter)
+            } else {
+                self.side_hit(ray, enter)
+            },
+            exit: if caps.1 < tube.1 {
+                self.cap_hit(last_cap, ray, exit)
+            } else {
+                self.side_hit(ray, exit)
+            },
+        }]
     }
 }
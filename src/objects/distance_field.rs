@@ -0,0 +1,256 @@
+use crate::objects::{Intersection, Object};
+use crate::raytracer::Ray;
+use crate::textures::Texture;
+use crate::type_aliases::{Normal, Point};
+use std::sync::Arc;
+
+/// A signed distance function: the distance from a point to the closest surface, negative inside
+/// the shape. It must never overestimate the distance, or the ray may step through the surface.
+pub type DistanceFunction = Arc<dyn Fn(Point) -> f64 + Send + Sync>;
+
+/// `Sdf` is a shape described by its signed distance function. Shapes can be combined with
+/// `union`, `subtract`, `intersect` and their smooth versions, which blend the surfaces together
+/// over a distance of `smoothness`.
+#[derive(Clone)]
+pub enum Sdf {
+    Sphere {
+        center: Point,
+        radius: f64,
+    },
+    /// A box with its edges rounded off by `radius`
+    RoundedBox {
+        center: Point,
+        half_size: Point,
+        radius: f64,
+    },
+    /// A ring lying flat in the XZ-plane
+    Torus {
+        center: Point,
+        major_radius: f64,
+        minor_radius: f64,
+    },
+    Capsule {
+        start: Point,
+        end: Point,
+        radius: f64,
+    },
+    /// The Mandelbulb fractal, about `2 * scale` wide. `power` 8.0 gives the classic shape.
+    Mandelbulb {
+        center: Point,
+        scale: f64,
+        power: f64,
+        iterations: u32,
+    },
+    Union(Box<Sdf>, Box<Sdf>),
+    Subtraction(Box<Sdf>, Box<Sdf>),
+    Intersection(Box<Sdf>, Box<Sdf>),
+    SmoothUnion(Box<Sdf>, Box<Sdf>, f64),
+    SmoothSubtraction(Box<Sdf>, Box<Sdf>, f64),
+    /// Any other distance function
+    Custom(DistanceFunction),
+}
+
+impl Sdf {
+    pub fn custom(distance: impl Fn(Point) -> f64 + Send + Sync + 'static) -> Self {
+        Sdf::Custom(Arc::new(distance))
+    }
+
+    pub fn union(self, other: Sdf) -> Self {
+        Sdf::Union(Box::new(self), Box::new(other))
+    }
+
+    /// Cut `other` out of this shape
+    pub fn subtract(self, other: Sdf) -> Self {
+        Sdf::Subtraction(Box::new(self), Box::new(other))
+    }
+
+    pub fn intersect(self, other: Sdf) -> Self {
+        Sdf::Intersection(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Sdf, smoothness: f64) -> Self {
+        Sdf::SmoothUnion(Box::new(self), Box::new(other), smoothness)
+    }
+
+    pub fn smooth_subtract(self, other: Sdf, smoothness: f64) -> Self {
+        Sdf::SmoothSubtraction(Box::new(self), Box::new(other), smoothness)
+    }
+
+    pub fn distance(&self, point: Point) -> f64 {
+        match self {
+            Sdf::Sphere { center, radius } => (point - center).norm() - radius,
+            Sdf::RoundedBox {
+                center,
+                half_size,
+                radius,
+            } => {
+                let q = (point - center).abs() - half_size.add_scalar(-radius);
+                q.sup(&Point::zeros()).norm() + q.max().min(0.0) - radius
+            }
+            Sdf::Torus {
+                center,
+                major_radius,
+                minor_radius,
+            } => {
+                let local = point - center;
+                let distance_from_ring = (local.x.hypot(local.z) - major_radius).hypot(local.y);
+                distance_from_ring - minor_radius
+            }
+            Sdf::Capsule { start, end, radius } => {
+                let axis = end - start;
+                let along = ((point - start).dot(&axis) / axis.norm_squared()).clamp(0.0, 1.0);
+                (point - start - axis * along).norm() - radius
+            }
+            Sdf::Mandelbulb {
+                center,
+                scale,
+                power,
+                iterations,
+            } => mandelbulb((point - center) / *scale, *power, *iterations) * scale,
+            Sdf::Union(a, b) => a.distance(point).min(b.distance(point)),
+            Sdf::Subtraction(a, b) => a.distance(point).max(-b.distance(point)),
+            Sdf::Intersection(a, b) => a.distance(point).max(b.distance(point)),
+            Sdf::SmoothUnion(a, b, smoothness) => {
+                smooth_min(a.distance(point), b.distance(point), *smoothness)
+            }
+            Sdf::SmoothSubtraction(a, b, smoothness) => {
+                -smooth_min(-a.distance(point), b.distance(point), *smoothness)
+            }
+            Sdf::Custom(distance) => distance(point),
+        }
+    }
+}
+
+/// Polynomial smooth minimum by [Inigo Quilez](https://iquilezles.org/articles/smin/)
+fn smooth_min(a: f64, b: f64, smoothness: f64) -> f64 {
+    if smoothness <= 0.0 {
+        return a.min(b);
+    }
+    let h = (smoothness - (a - b).abs()).max(0.0) / smoothness;
+    a.min(b) - h * h * smoothness / 4.0
+}
+
+/// Distance estimate for the Mandelbulb with its center at the origin
+fn mandelbulb(point: Point, power: f64, iterations: u32) -> f64 {
+    let mut z = point;
+    let mut derivative = 1.0;
+    let mut radius = z.norm();
+
+    for _ in 0..iterations {
+        if !(1e-12..=2.0).contains(&radius) {
+            break;
+        }
+
+        // Raise z to `power` in spherical coordinates
+        let theta = (z.z / radius).acos() * power;
+        let phi = z.y.atan2(z.x) * power;
+        derivative = radius.powf(power - 1.0) * power * derivative + 1.0;
+        z = Point::new(
+            theta.sin() * phi.cos(),
+            theta.sin() * phi.sin(),
+            theta.cos(),
+        ) * radius.powf(power)
+            + point;
+        radius = z.norm();
+    }
+
+    0.5 * radius.max(1e-12).ln() * radius / derivative
+}
+
+/// `DistanceField` renders an `Sdf` by sphere tracing: the ray repeatedly steps forward by the
+/// distance to the closest surface until it is closer than `epsilon`.
+///
+/// `max_steps` and `max_distance` stop rays that pass close to the surface without hitting it.
+pub struct DistanceField {
+    pub sdf: Sdf,
+    pub texture: Texture,
+    pub epsilon: f64,
+    pub max_steps: u32,
+    pub max_distance: f64,
+}
+
+impl DistanceField {
+    pub fn new(sdf: Sdf, texture: Texture) -> Self {
+        Self {
+            sdf,
+            texture,
+            epsilon: 1e-4,
+            max_steps: 256,
+            max_distance: 1000.0,
+        }
+    }
+
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn with_max_distance(mut self, max_distance: f64) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Gradient of the distance function, estimated with the tetrahedron technique. Where the
+    /// field is flat, e.g. at the center of a sphere or on a ridge, `fallback` is used instead.
+    fn normal(&self, point: Point, fallback: Normal) -> Normal {
+        let h = self.epsilon;
+        let offsets = [
+            Point::new(1.0, -1.0, -1.0),
+            Point::new(-1.0, -1.0, 1.0),
+            Point::new(-1.0, 1.0, -1.0),
+            Point::new(1.0, 1.0, 1.0),
+        ];
+
+        offsets
+            .iter()
+            .map(|offset| offset * self.sdf.distance(point + offset * h))
+            .sum::<Point>()
+            .try_normalize(1e-12)
+            .unwrap_or(fallback)
+    }
+}
+
+impl Object for DistanceField {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        // Rays starting inside the shape march towards the surface from the inside
+        let side = if self.sdf.distance(ray.origin) < -self.epsilon {
+            -1.0
+        } else {
+            1.0
+        };
+        let max_distance = self.max_distance.min(ray.intersection_dist);
+        // Step off the surface first when the ray starts on it, e.g. after a reflection
+        let min_distance = 10.0 * self.epsilon;
+
+        let mut distance = 0.0;
+        for _ in 0..self.max_steps {
+            if distance > max_distance {
+                return None;
+            }
+
+            let point = ray.origin + ray.direction * distance;
+            let step = side * self.sdf.distance(point);
+
+            if step < self.epsilon && distance > min_distance {
+                return Some(Intersection::new(
+                    point,
+                    self.normal(point, -ray.direction),
+                    distance,
+                    self.texture(),
+                ));
+            }
+            distance += step.abs().max(self.epsilon);
+        }
+
+        None
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture.clone()
+    }
+}
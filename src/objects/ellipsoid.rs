@@ -0,0 +1,87 @@
+use super::Texture;
+use crate::objects::{discriminant, tangent_frame, Intersection, Object};
+use crate::raytracer::Ray;
+use crate::type_aliases::{Direction, Directions, Normal, Point, Uv};
+use std::f64::consts::PI;
+
+/// `Ellipsoid` is a sphere stretched by a different radius along each axis.
+#[derive(Debug, Clone)]
+pub struct Ellipsoid {
+    pub center: Point,
+    pub radii: Point,
+    pub texture: Texture,
+}
+
+impl Ellipsoid {
+    pub fn new(center: Point, radii: Point, texture: Texture) -> Self {
+        Self {
+            center,
+            radii,
+            texture,
+        }
+    }
+
+    /// The gradient of `(x/a)² + (y/b)² + (z/c)²`
+    fn normal(&self, point: Point) -> Normal {
+        let local = point - self.center;
+        local
+            .component_div(&self.radii.component_mul(&self.radii))
+            .normalize()
+    }
+
+    /// Spherical coordinates of the point on the unit sphere the ellipsoid is stretched from
+    fn uv(&self, point: Point) -> Uv {
+        let direction = (point - self.center).component_div(&self.radii).normalize();
+        Uv::new(
+            0.5 + direction.z.atan2(direction.x) / (2.0 * PI),
+            0.5 + direction.y.clamp(-1.0, 1.0).asin() / PI,
+        )
+    }
+
+    /// Directions in which `u` and `v` grow, those of the unit sphere stretched by the radii.
+    /// At the poles `u` is undefined, so any direction along the surface is used.
+    fn tangents(&self, point: Point, normal: Normal) -> (Direction, Direction) {
+        let direction = (point - self.center).component_div(&self.radii).normalize();
+        let around = Direction::new(-direction.z, 0.0, direction.x).component_mul(&self.radii);
+        let up = (Direction::up() - direction * direction.y).component_mul(&self.radii);
+        match (around.try_normalize(1e-9), up.try_normalize(1e-9)) {
+            (Some(tangent), Some(bitangent)) => (tangent, bitangent),
+            _ => tangent_frame(normal),
+        }
+    }
+}
+
+impl Object for Ellipsoid {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        // Squash the ray so the ellipsoid becomes a unit sphere. Distances along the ray stay
+        // the same.
+        let origin = (ray.origin - self.center).component_div(&self.radii);
+        let direction = ray.direction.component_div(&self.radii);
+
+        let a = direction.dot(&direction);
+        let b = 2.0 * origin.dot(&direction);
+        let c = origin.dot(&origin) - 1.0;
+        let sqrt_discriminant = discriminant(a, b, c)?.sqrt();
+
+        let dist = [
+            (-b - sqrt_discriminant) / (2.0 * a),
+            (-b + sqrt_discriminant) / (2.0 * a),
+        ]
+        .into_iter()
+        .find(|dist| (1e-6..ray.intersection_dist).contains(dist))?;
+
+        let hit_point = ray.origin + ray.direction * dist;
+        let normal = self.normal(hit_point);
+        let (tangent, bitangent) = self.tangents(hit_point, normal);
+        Some(
+            Intersection::new(hit_point, normal, dist, self.texture())
+                .with_local_point(hit_point - self.center)
+                .with_uv(self.uv(hit_point))
+                .with_tangents(tangent, bitangent),
+        )
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture.clone()
+    }
+}
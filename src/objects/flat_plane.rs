@@ -1,6 +1,6 @@
 use crate::objects::{Intersection, Object};
 use crate::raytracer::Ray;
-use crate::type_aliases::{Directions, Normal, Point};
+use crate::type_aliases::{Direction, Directions, Normal, Point, Uv};
 
 use super::Texture;
 
@@ -26,6 +26,29 @@ impl FlatPlane {
             Normal::up()
         }
     }
+
+    /// Planar mapping of the disk's bounding square, seen from above with the Z-axis pointing
+    /// down in the image. Seen from below `u` is mirrored, so the image is not.
+    fn uv(&self, point: Point, normal: Normal) -> Uv {
+        let local = (point - self.center) / (2.0 * self.radius);
+        let u = if normal.y < 0.0 {
+            0.5 - local.x
+        } else {
+            local.x + 0.5
+        };
+        Uv::new(u, 0.5 - local.z)
+    }
+
+    /// The intersection at `dist` along `ray`, on the side of the disk `normal` points to
+    pub fn hit(&self, ray: &Ray, dist: f64, normal: Normal) -> Intersection {
+        let hit_point = ray.origin + ray.direction * dist;
+        // `v` grows towards -Z on both sides, `u` towards +X from above and -X from below
+        let bitangent = Direction::new(0.0, 0.0, -1.0);
+        Intersection::new(hit_point * (1.0 + 1e-6), normal, dist, self.texture())
+            .with_local_point(hit_point - self.center)
+            .with_uv(self.uv(hit_point, normal))
+            .with_tangents(bitangent.cross(&normal), bitangent)
+    }
 }
 
 impl Object for FlatPlane {
@@ -44,17 +67,12 @@ impl Object for FlatPlane {
         let hit_point = ray.origin + ray.direction * dist;
 
         if (hit_point - self.center).norm() <= self.radius {
-            return Some(Intersection::new(
-                hit_point * (1.0 + 1e-6),
-                normal,
-                dist,
-                self.texture(),
-            ));
+            return Some(self.hit(ray, dist, normal));
         }
 
         None
     }
     fn texture(&self) -> Texture {
-        self.texture
+        self.texture.clone()
     }
 }
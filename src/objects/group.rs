@@ -0,0 +1,126 @@
+use crate::objects::{Instance, Intersection, Object, Objects, Transform};
+use crate::raytracer::Ray;
+use crate::textures::Texture;
+use crate::type_aliases::Color;
+use std::sync::Arc;
+
+/// A child of a `Group`
+#[derive(Clone)]
+pub enum SceneNode {
+    Object(Arc<dyn Object>),
+    Group(Box<Group>),
+}
+
+/// `Group` is a named collection of objects and other groups that move together, e.g. a table
+/// made of a cube and four cylinders.
+///
+/// The children are placed relative to the group, and `transform` moves the group and
+/// everything in it. Groups can be nested and found by name with `find`.
+#[derive(Clone)]
+pub struct Group {
+    pub name: String,
+    pub transform: Transform,
+    pub children: Vec<SceneNode>,
+}
+
+impl Group {
+    pub fn new(name: impl Into<String>, transform: Transform) -> Self {
+        Self {
+            name: name.into(),
+            transform,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_object(mut self, object: Arc<dyn Object>) -> Self {
+        self.children.push(SceneNode::Object(object));
+        self
+    }
+
+    pub fn with_group(mut self, group: Group) -> Self {
+        self.children.push(SceneNode::Group(Box::new(group)));
+        self
+    }
+
+    /// This group or the first group inside it called `name`
+    pub fn find(&self, name: &str) -> Option<&Group> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.subgroups().find_map(|group| group.find(name))
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Group> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|child| match child {
+            SceneNode::Group(group) => group.find_mut(name),
+            SceneNode::Object(_) => None,
+        })
+    }
+
+    fn subgroups(&self) -> impl Iterator<Item = &Group> {
+        self.children.iter().filter_map(|child| match child {
+            SceneNode::Group(group) => Some(group.as_ref()),
+            SceneNode::Object(_) => None,
+        })
+    }
+
+    /// Every object in the group and its subgroups, each placed in the scene by an `Instance`
+    pub fn flatten(&self) -> Objects {
+        let mut objects = Vec::new();
+        self.flatten_into(&Transform::identity(), &mut objects);
+        objects
+    }
+
+    fn flatten_into(&self, parent: &Transform, objects: &mut Objects) {
+        let transform = self.transform.then(parent);
+        for child in &self.children {
+            match child {
+                SceneNode::Object(object) => {
+                    objects.push(Arc::new(Instance::new(object.clone(), transform)));
+                }
+                SceneNode::Group(group) => group.flatten_into(&transform, objects),
+            }
+        }
+    }
+
+    /// The closest object hit by `ray`, as it was added to the group or its subgroups, and where
+    /// it was hit in the scene
+    pub fn hit_object(&self, ray: &Ray) -> Option<(Arc<dyn Object>, Intersection)> {
+        let (mut local_ray, stretch) = self.transform.ray_to_local(ray);
+
+        let mut closest_hit = None;
+        for child in &self.children {
+            let hit = match child {
+                SceneNode::Object(object) => object
+                    .intersection(&local_ray)
+                    .map(|intersection| (object.clone(), intersection)),
+                SceneNode::Group(group) => group.hit_object(&local_ray),
+            };
+            if let Some((object, intersection)) = hit {
+                if intersection.distance < local_ray.intersection_dist {
+                    local_ray.intersection_dist = intersection.distance;
+                    closest_hit = Some((object, intersection));
+                }
+            }
+        }
+
+        closest_hit.map(|(object, intersection)| {
+            let intersection = self.transform.intersection_to_world(intersection, stretch);
+            (object, intersection)
+        })
+    }
+}
+
+impl Object for Group {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        self.hit_object(ray).map(|(_, intersection)| intersection)
+    }
+
+    /// Groups have no texture of their own, every child keeps its own
+    fn texture(&self) -> Texture {
+        Texture::Diffusive(Color::default())
+    }
+}
@@ -0,0 +1,354 @@
+use crate::objects::{Intersection, Object};
+use crate::raytracer::Ray;
+use crate::textures::{perlin_noise, Image, Texture};
+use crate::type_aliases::{Direction, Normal, Point, Uv};
+use std::io;
+
+/// `HeightMap` is a grid of heights between 0.0 and 1.0, stored row by row. The first row is
+/// the far edge of the terrain, like the top row of an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightMap {
+    pub columns: usize,
+    pub rows: usize,
+    pub heights: Vec<f64>,
+}
+
+impl HeightMap {
+    /// Heights from the brightness of the pixels. White is the highest.
+    pub fn from_image(image: &Image) -> Self {
+        Self {
+            columns: image.width as usize,
+            rows: image.height as usize,
+            heights: image
+                .pixels
+                .iter()
+                .map(|color| (color.x + color.y + color.z) / (3.0 * 255.0))
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        Ok(Self::from_image(&Image::load(path)?))
+    }
+
+    /// Rolling hills from layers of Perlin noise. `scale` is the number of hills across the
+    /// map and every octave adds finer detail at half the strength.
+    pub fn from_noise(columns: usize, rows: usize, scale: f64, octaves: u32) -> Self {
+        let mut heights = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let point = Point::new(
+                    column as f64 / columns as f64,
+                    0.5,
+                    row as f64 / rows as f64,
+                ) * scale;
+
+                let mut height = 0.0;
+                let mut weight = 1.0;
+                for octave in 0..octaves {
+                    height += weight * perlin_noise(point * 2f64.powi(octave as i32));
+                    weight *= 0.5;
+                }
+                heights.push(height);
+            }
+        }
+
+        // Stretch the heights to fill 0.0 to 1.0
+        let min = heights.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let range = (max - min).max(1e-12);
+        for height in heights.iter_mut() {
+            *height = (*height - min) / range;
+        }
+
+        Self {
+            columns,
+            rows,
+            heights,
+        }
+    }
+
+    fn get(&self, column: usize, row: usize) -> f64 {
+        self.heights[row * self.columns + column]
+    }
+}
+
+/// `Heightfield` is a terrain made of triangles over a square of `size` centered on `center`,
+/// rising up to `height` above it. It can replace a `FlatPlane` as the ground.
+///
+/// Rays walk through the grid one cell at a time, and only test the triangles of cells whose
+/// height range they pass through. Normals are blended from the corners of each triangle, so
+/// the terrain looks smooth.
+pub struct Heightfield {
+    pub center: Point,
+    pub size: f64,
+    pub height: f64,
+    pub map: HeightMap,
+    pub texture: Texture,
+    vertices: Vec<Point>,
+    normals: Vec<Normal>,
+    /// Lowest and highest point of every cell
+    cell_bounds: Vec<(f64, f64)>,
+}
+
+impl Heightfield {
+    /// Fails if `map` has fewer than 2x2 heights, e.g. when it was loaded from an image that is
+    /// only one pixel wide or high.
+    pub fn new(
+        center: Point,
+        size: f64,
+        height: f64,
+        map: HeightMap,
+        texture: Texture,
+    ) -> io::Result<Self> {
+        if map.columns < 2 || map.rows < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "a height map needs at least 2x2 heights, not {}x{}",
+                    map.columns, map.rows
+                ),
+            ));
+        }
+        if map.heights.len() != map.columns * map.rows {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the number of heights does not match the size of the height map",
+            ));
+        }
+
+        let cell_size = (
+            size / (map.columns - 1) as f64,
+            size / (map.rows - 1) as f64,
+        );
+        let corner = center - Point::new(size / 2.0, 0.0, size / 2.0);
+
+        let mut vertices = Vec::with_capacity(map.heights.len());
+        for row in 0..map.rows {
+            for column in 0..map.columns {
+                vertices.push(
+                    corner
+                        + Point::new(
+                            column as f64 * cell_size.0,
+                            map.get(column, row) * height,
+                            row as f64 * cell_size.1,
+                        ),
+                );
+            }
+        }
+
+        // Slopes from the neighbouring heights
+        let mut normals = Vec::with_capacity(map.heights.len());
+        for row in 0..map.rows {
+            for column in 0..map.columns {
+                let [left, right] = [column.saturating_sub(1), (column + 1).min(map.columns - 1)];
+                let [near, far] = [row.saturating_sub(1), (row + 1).min(map.rows - 1)];
+                let slope_x = (map.get(right, row) - map.get(left, row)) * height
+                    / ((right - left) as f64 * cell_size.0);
+                let slope_z = (map.get(column, far) - map.get(column, near)) * height
+                    / ((far - near) as f64 * cell_size.1);
+                normals.push(Normal::new(-slope_x, 1.0, -slope_z).normalize());
+            }
+        }
+
+        let mut cell_bounds = Vec::with_capacity((map.columns - 1) * (map.rows - 1));
+        for row in 0..map.rows - 1 {
+            for column in 0..map.columns - 1 {
+                let corners = [
+                    map.get(column, row),
+                    map.get(column + 1, row),
+                    map.get(column, row + 1),
+                    map.get(column + 1, row + 1),
+                ];
+                let min = corners.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = corners.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                cell_bounds.push((center.y + min * height, center.y + max * height));
+            }
+        }
+
+        Ok(Self {
+            center,
+            size,
+            height,
+            map,
+            texture,
+            vertices,
+            normals,
+            cell_bounds,
+        })
+    }
+
+    fn corner(&self) -> Point {
+        self.center - Point::new(self.size / 2.0, 0.0, self.size / 2.0)
+    }
+
+    fn cell_size(&self) -> (f64, f64) {
+        (
+            self.size / (self.map.columns - 1) as f64,
+            self.size / (self.map.rows - 1) as f64,
+        )
+    }
+
+    /// Distances where the ray enters and leaves the bounding box of the terrain
+    fn bounding_box_distances(&self, ray: &Ray) -> Option<(f64, f64)> {
+        let min = self.corner();
+        let max = min + Point::new(self.size, self.height, self.size);
+
+        let mut enter = 0.0_f64;
+        let mut exit = ray.intersection_dist;
+        for axis in 0..3 {
+            let inverse = 1.0 / ray.direction[axis];
+            let t1 = (min[axis] - ray.origin[axis]) * inverse;
+            let t2 = (max[axis] - ray.origin[axis]) * inverse;
+            enter = enter.max(t1.min(t2));
+            exit = exit.min(t1.max(t2));
+        }
+
+        (enter <= exit).then_some((enter, exit))
+    }
+
+    /// Closest hit on the two triangles of a cell
+    fn intersect_cell(
+        &self,
+        ray: &Ray,
+        column: usize,
+        row: usize,
+    ) -> Option<(f64, [f64; 3], [usize; 3])> {
+        let index = |column: usize, row: usize| row * self.map.columns + column;
+        let [a, b, c, d] = [
+            index(column, row),
+            index(column + 1, row),
+            index(column, row + 1),
+            index(column + 1, row + 1),
+        ];
+
+        [[a, c, b], [b, c, d]]
+            .into_iter()
+            .filter_map(|triangle| {
+                let (distance, weights) = self.intersect_triangle(ray, triangle)?;
+                Some((distance, weights, triangle))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    /// Möller-Trumbore ray-triangle intersection, returning the distance and the barycentric
+    /// weights of the corners
+    fn intersect_triangle(&self, ray: &Ray, triangle: [usize; 3]) -> Option<(f64, [f64; 3])> {
+        let [a, b, c] = triangle.map(|i| self.vertices[i]);
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+        let p = ray.direction.cross(&edge_2);
+        let determinant = edge_1.dot(&p);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+
+        let inverse = 1.0 / determinant;
+        let offset = ray.origin - a;
+        let u = offset.dot(&p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = offset.cross(&edge_1);
+        let v = ray.direction.dot(&q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge_2.dot(&q) * inverse;
+        (1e-6..ray.intersection_dist)
+            .contains(&distance)
+            .then_some((distance, [1.0 - u - v, u, v]))
+    }
+}
+
+impl Object for Heightfield {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let (enter, exit) = self.bounding_box_distances(ray)?;
+        let corner = self.corner();
+        let cell_size = self.cell_size();
+        let cells = (self.map.columns - 1, self.map.rows - 1);
+
+        // Grid DDA: find the cell where the ray enters and step through the cells it crosses
+        let start = ray.origin + ray.direction * enter - corner;
+        let mut column = ((start.x / cell_size.0).floor().max(0.0) as usize).min(cells.0 - 1);
+        let mut row = ((start.z / cell_size.1).floor().max(0.0) as usize).min(cells.1 - 1);
+
+        let axis_step = |direction: f64, position: f64, cell: usize, size: f64| {
+            if direction > 0.0 {
+                (
+                    1,
+                    ((cell + 1) as f64 * size - position) / direction,
+                    size / direction,
+                )
+            } else if direction < 0.0 {
+                (
+                    -1,
+                    (cell as f64 * size - position) / direction,
+                    -size / direction,
+                )
+            } else {
+                (0, f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (step_x, mut next_x, delta_x) =
+            axis_step(ray.direction.x, start.x, column, cell_size.0);
+        let (step_z, mut next_z, delta_z) = axis_step(ray.direction.z, start.z, row, cell_size.1);
+
+        let mut cell_enter = enter;
+        loop {
+            let cell_exit = (enter + next_x.min(next_z)).min(exit);
+
+            // Skip cells where the ray passes above or below all of the terrain
+            let (min, max) = self.cell_bounds[row * cells.0 + column];
+            let y_enter = ray.origin.y + ray.direction.y * cell_enter;
+            let y_exit = ray.origin.y + ray.direction.y * cell_exit;
+            if y_enter.min(y_exit) <= max && y_enter.max(y_exit) >= min {
+                if let Some((distance, weights, triangle)) = self.intersect_cell(ray, column, row) {
+                    let normal = triangle
+                        .iter()
+                        .zip(weights)
+                        .map(|(&vertex, weight)| self.normals[vertex] * weight)
+                        .sum::<Normal>()
+                        .normalize();
+                    let hit_point = ray.origin + ray.direction * distance;
+                    let local = hit_point - corner;
+
+                    return Some(
+                        Intersection::new(hit_point, normal, distance, self.texture())
+                            .with_local_point(hit_point - self.center)
+                            .with_uv(Uv::new(local.x / self.size, 1.0 - local.z / self.size))
+                            .with_tangents(
+                                Direction::new(1.0, 0.0, 0.0),
+                                Direction::new(0.0, 0.0, -1.0),
+                            ),
+                    );
+                }
+            }
+
+            if cell_exit >= exit {
+                return None;
+            }
+
+            cell_enter = cell_exit;
+            if next_x < next_z {
+                next_x += delta_x;
+                match column.checked_add_signed(step_x) {
+                    Some(next) if next < cells.0 => column = next,
+                    _ => return None,
+                }
+            } else {
+                next_z += delta_z;
+                match row.checked_add_signed(step_z) {
+                    Some(next) if next < cells.1 => row = next,
+                    _ => return None,
+                }
+            }
+        }
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture.clone()
+    }
+}
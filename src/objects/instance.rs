@@ -0,0 +1,63 @@
+use crate::objects::{Intersection, Object, Span, Transform};
+use crate::raytracer::Ray;
+use crate::textures::Texture;
+use std::sync::Arc;
+
+/// `Instance` places a shared object somewhere else in the scene. Many instances can use the
+/// same object, so its geometry is only stored once.
+///
+/// `material` replaces the texture of the object when set.
+pub struct Instance {
+    pub object: Arc<dyn Object>,
+    pub transform: Transform,
+    pub material: Option<Texture>,
+}
+
+impl Instance {
+    pub fn new(object: Arc<dyn Object>, transform: Transform) -> Self {
+        Self {
+            object,
+            transform,
+            material: None,
+        }
+    }
+
+    pub fn with_material(mut self, material: Texture) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    fn to_world(&self, intersection: Intersection, stretch: f64) -> Intersection {
+        let mut intersection = self.transform.intersection_to_world(intersection, stretch);
+        if let Some(material) = &self.material {
+            intersection.texture = material.clone();
+        }
+        intersection
+    }
+}
+
+impl Object for Instance {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let (object_ray, stretch) = self.transform.ray_to_local(ray);
+        let intersection = self.object.intersection(&object_ray)?;
+        Some(self.to_world(intersection, stretch))
+    }
+
+    fn texture(&self) -> Texture {
+        self.material
+            .clone()
+            .unwrap_or_else(|| self.object.texture())
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let (object_ray, stretch) = self.transform.ray_to_local(ray);
+        self.object
+            .spans(&object_ray)
+            .into_iter()
+            .map(|span| Span {
+                enter: self.to_world(span.enter, stretch),
+                exit: self.to_world(span.exit, stretch),
+            })
+            .collect()
+    }
+}
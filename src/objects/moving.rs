@@ -0,0 +1,136 @@
+use crate::objects::{Intersection, Object, Span};
+use crate::raytracer::Ray;
+use crate::textures::Texture;
+use crate::type_aliases::{Direction, Point};
+use std::sync::Arc;
+
+/// How far something has moved at a given time
+#[derive(Debug, Clone, PartialEq)]
+pub enum Motion {
+    /// Moving in a straight line, `velocity` per unit of time
+    Linear { velocity: Direction },
+    /// Offsets at given times, sorted by time. Between two keyframes the offset is interpolated
+    /// linearly, and before the first and after the last keyframe it stays the same.
+    Keyframes(Vec<(f64, Point)>),
+}
+
+impl Motion {
+    pub fn linear(velocity: Direction) -> Self {
+        Motion::Linear { velocity }
+    }
+
+    pub fn keyframes(mut keyframes: Vec<(f64, Point)>) -> Self {
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Motion::Keyframes(keyframes)
+    }
+
+    pub fn offset_at(&self, time: f64) -> Point {
+        match self {
+            Motion::Linear { velocity } => velocity * time,
+            Motion::Keyframes(keyframes) => {
+                let next = keyframes.partition_point(|(key_time, _)| *key_time <= time);
+                match (next.checked_sub(1), keyframes.get(next)) {
+                    (None, None) => Point::zeros(),
+                    (None, Some((_, offset))) => *offset,
+                    (Some(last), None) => keyframes[last].1,
+                    (Some(previous), Some((next_time, next_offset))) => {
+                        let (previous_time, previous_offset) = keyframes[previous];
+                        let t = (time - previous_time) / (next_time - previous_time);
+                        previous_offset.lerp(next_offset, t)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `Moving` makes any object move during the camera's shutter interval, so it is blurred along
+/// its path. Rays find the object where it is at `ray.time`.
+pub struct Moving {
+    pub object: Arc<dyn Object>,
+    pub motion: Motion,
+}
+
+impl Moving {
+    pub fn new(object: Arc<dyn Object>, motion: Motion) -> Self {
+        Self { object, motion }
+    }
+
+    /// The ray moved the opposite way, so the object can be intersected where it started
+    fn local_ray(&self, ray: &Ray) -> (Ray, Point) {
+        let offset = self.motion.offset_at(ray.time);
+        let mut local_ray =
+            Ray::new(ray.origin - offset, ray.direction, ray.depth).with_time(ray.time);
+        local_ray.intersection_dist = ray.intersection_dist;
+        local_ray.sample = ray.sample;
+        (local_ray, offset)
+    }
+}
+
+impl Object for Moving {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let (local_ray, offset) = self.local_ray(ray);
+        let mut intersection = self.object.intersection(&local_ray)?;
+        intersection.hit_point += offset;
+        Some(intersection)
+    }
+
+    fn texture(&self) -> Texture {
+        self.object.texture()
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let (local_ray, offset) = self.local_ray(ray);
+        let mut spans = self.object.spans(&local_ray);
+        for span in spans.iter_mut() {
+            span.enter.hit_point += offset;
+            span.exit.hit_point += offset;
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Sphere;
+
+    #[test]
+    fn linear_motion_follows_the_velocity() {
+        let motion = Motion::linear(Direction::new(1.0, 0.0, -2.0));
+        assert_eq!(motion.offset_at(0.0), Point::zeros());
+        assert_eq!(motion.offset_at(0.5), Point::new(0.5, 0.0, -1.0));
+    }
+
+    #[test]
+    fn keyframes_are_interpolated_and_held() {
+        // Sorted by `keyframes`
+        let motion = Motion::keyframes(vec![
+            (2.0, Point::new(4.0, 0.0, 0.0)),
+            (1.0, Point::new(2.0, 0.0, 0.0)),
+        ]);
+        assert_eq!(motion.offset_at(0.0), Point::new(2.0, 0.0, 0.0));
+        assert_eq!(motion.offset_at(1.5), Point::new(3.0, 0.0, 0.0));
+        assert_eq!(motion.offset_at(2.0), Point::new(4.0, 0.0, 0.0));
+        assert_eq!(motion.offset_at(9.0), Point::new(4.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn no_keyframes_stay_in_place() {
+        assert_eq!(Motion::keyframes(Vec::new()).offset_at(1.0), Point::zeros());
+    }
+
+    #[test]
+    fn rays_find_the_object_where_it_is_at_their_time() {
+        let sphere = Arc::new(Sphere::new(Point::zeros(), 1.0, Texture::Reflective));
+        let moving = Moving::new(sphere, Motion::linear(Direction::new(0.0, 4.0, 0.0)));
+        let ray = |time| {
+            Ray::new(Point::new(0.0, 2.0, 5.0), Point::new(0.0, 0.0, -1.0), 0).with_time(time)
+        };
+
+        assert!(moving.intersection(&ray(0.0)).is_none());
+        let hit = moving.intersection(&ray(0.5)).unwrap();
+        assert!((hit.distance - 4.0).abs() < 1e-9);
+        assert!((hit.hit_point - Point::new(0.0, 2.0, 1.0)).norm() < 1e-9);
+    }
+}
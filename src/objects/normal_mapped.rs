@@ -0,0 +1,28 @@
+use crate::objects::{Intersection, Object};
+use crate::raytracer::Ray;
+use crate::textures::{NormalMap, Texture};
+use std::sync::Arc;
+
+/// `NormalMapped` adds a bump or normal map to any object.
+pub struct NormalMapped {
+    pub object: Arc<dyn Object>,
+    pub normal_map: NormalMap,
+}
+
+impl NormalMapped {
+    pub fn new(object: Arc<dyn Object>, normal_map: NormalMap) -> Self {
+        Self { object, normal_map }
+    }
+}
+
+impl Object for NormalMapped {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let mut intersection = self.object.intersection(ray)?;
+        intersection.normal = self.normal_map.perturb(&intersection);
+        Some(intersection)
+    }
+
+    fn texture(&self) -> Texture {
+        self.object.texture()
+    }
+}
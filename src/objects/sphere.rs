@@ -1,6 +1,7 @@
-use crate::objects::{Intersection, Object};
+use crate::objects::{tangent_frame, Intersection, Object, Span};
 use crate::raytracer::Ray;
-use crate::type_aliases::{Normal, Point};
+use crate::type_aliases::{Direction, Directions, Normal, Point, Uv};
+use std::f64::consts::PI;
 
 use super::Texture;
 
@@ -22,10 +23,18 @@ impl Sphere {
     fn normal(&self, point: Point) -> Normal {
         (point - self.center).normalize()
     }
-}
 
-impl Object for Sphere {
-    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+    /// Spherical coordinates, with `u` going around the Y-axis and `v` from bottom to top
+    fn uv(&self, point: Point) -> Uv {
+        let direction = self.normal(point);
+        Uv::new(
+            0.5 + direction.z.atan2(direction.x) / (2.0 * PI),
+            0.5 + direction.y.clamp(-1.0, 1.0).asin() / PI,
+        )
+    }
+
+    /// Distances along the ray to both sides of the sphere, the closest first
+    fn distances(&self, ray: &Ray) -> Option<(f64, f64)> {
         let origin_to_center = ray.origin - self.center;
         let a = ray.direction.dot(&ray.direction);
         let b = 2.0 * origin_to_center.dot(&ray.direction);
@@ -39,6 +48,33 @@ impl Object for Sphere {
         let sqrt_discriminant = discriminant.sqrt();
         let dist_1 = (-b - sqrt_discriminant) / (2.0 * a);
         let dist_2 = (-b + sqrt_discriminant) / (2.0 * a);
+        Some((dist_1, dist_2))
+    }
+
+    fn hit(&self, ray: &Ray, dist: f64) -> Intersection {
+        let hit_point = ray.origin + dist * ray.direction;
+        let normal = self.normal(hit_point);
+        let (tangent, bitangent) = self.tangents(normal);
+        Intersection::new(hit_point, normal, dist, self.texture())
+            .with_local_point(hit_point - self.center)
+            .with_uv(self.uv(hit_point))
+            .with_tangents(tangent, bitangent)
+    }
+
+    /// Directions in which `u` and `v` grow. At the poles `u` is undefined, so any direction
+    /// along the surface is used.
+    fn tangents(&self, normal: Normal) -> (Direction, Direction) {
+        let around = Direction::new(-normal.z, 0.0, normal.x);
+        match around.try_normalize(1e-9) {
+            Some(tangent) => (tangent, (Direction::up() - normal * normal.y).normalize()),
+            None => tangent_frame(normal),
+        }
+    }
+}
+
+impl Object for Sphere {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let (dist_1, dist_2) = self.distances(ray)?;
 
         let dist = if (1e-6..dist_2).contains(&dist_1) {
             dist_1 // dist 1 is closer
@@ -47,19 +83,23 @@ impl Object for Sphere {
         };
 
         if (1e-6..ray.intersection_dist).contains(&dist) {
-            let hit_point = ray.origin + dist * ray.direction;
-            return Some(Intersection::new(
-                hit_point,
-                self.normal(hit_point),
-                dist,
-                self.texture(),
-            ));
+            return Some(self.hit(ray, dist));
         }
 
         None
     }
 
     fn texture(&self) -> Texture {
-        self.texture
+        self.texture.clone()
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        match self.distances(ray) {
+            Some((dist_1, dist_2)) if dist_2 > 1e-6 => vec![Span {
+                enter: self.hit(ray, dist_1.max(0.0)),
+                exit: self.hit(ray, dist_2),
+            }],
+            _ => Vec::new(),
+        }
     }
 }
@@ -0,0 +1,255 @@
+use super::Texture;
+use crate::objects::{tangent_frame, Intersection, Object};
+use crate::raytracer::Ray;
+use crate::type_aliases::{Direction, Normal, Point, Uv};
+use std::f64::consts::PI;
+
+/// `Torus` is a ring lying flat in the XZ-plane. `major_radius` is the distance from `center`
+/// to the middle of the tube and `minor_radius` the radius of the tube.
+#[derive(Debug, Clone)]
+pub struct Torus {
+    pub center: Point,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub texture: Texture,
+}
+
+impl Torus {
+    pub fn new(center: Point, major_radius: f64, minor_radius: f64, texture: Texture) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            texture,
+        }
+    }
+
+    /// Points away from the closest point on the circle in the middle of the tube
+    fn normal(&self, point: Point) -> Normal {
+        let local = point - self.center;
+        let around = Point::new(local.x, 0.0, local.z)
+            .try_normalize(1e-9)
+            .unwrap_or_default();
+        (local - around * self.major_radius).normalize()
+    }
+
+    /// `u` goes around the ring and `v` around the tube, starting on the outside
+    fn uv(&self, point: Point) -> Uv {
+        let local = point - self.center;
+        let distance_from_axis = (local.x.powi(2) + local.z.powi(2)).sqrt();
+        Uv::new(
+            0.5 + local.z.atan2(local.x) / (2.0 * PI),
+            0.5 + local.y.atan2(distance_from_axis - self.major_radius) / (2.0 * PI),
+        )
+    }
+
+    /// Directions in which `u` and `v` grow, around the ring and around the tube
+    fn tangents(&self, point: Point, normal: Normal) -> (Direction, Direction) {
+        let local = point - self.center;
+        match Direction::new(-local.z, 0.0, local.x).try_normalize(1e-9) {
+            Some(tangent) => (tangent, tangent.cross(&normal)),
+            None => tangent_frame(normal),
+        }
+    }
+}
+
+impl Object for Torus {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let direction = ray.direction;
+
+        // Start the ray close to the torus to keep the quartic accurate for far away rays
+        let bounding_radius = self.major_radius + self.minor_radius;
+        let skipped = ((self.center - ray.origin).dot(&direction) - bounding_radius).max(0.0);
+        let origin = ray.origin + direction * skipped - self.center;
+
+        // (|p|² + R² - r²)² = 4R²(x² + z²) with p = origin + t * direction
+        let major_squared = self.major_radius.powi(2);
+        let f = origin.dot(&direction);
+        let k = origin.dot(&origin) + major_squared - self.minor_radius.powi(2);
+        let roots = solve_quartic(
+            4.0 * f,
+            4.0 * f * f + 2.0 * k
+                - 4.0 * major_squared * (direction.x.powi(2) + direction.z.powi(2)),
+            4.0 * f * k - 8.0 * major_squared * (origin.x * direction.x + origin.z * direction.z),
+            k * k - 4.0 * major_squared * (origin.x.powi(2) + origin.z.powi(2)),
+        );
+
+        let dist = roots
+            .into_iter()
+            .map(|root| root + skipped)
+            .filter(|dist| (1e-6..ray.intersection_dist).contains(dist))
+            .min_by(|a, b| a.total_cmp(b))?;
+
+        let hit_point = ray.origin + direction * dist;
+        let normal = self.normal(hit_point);
+        let (tangent, bitangent) = self.tangents(hit_point, normal);
+        Some(
+            Intersection::new(hit_point, normal, dist, self.texture())
+                .with_local_point(hit_point - self.center)
+                .with_uv(self.uv(hit_point))
+                .with_tangents(tangent, bitangent),
+        )
+    }
+
+    fn texture(&self) -> Texture {
+        self.texture.clone()
+    }
+}
+
+/// Real roots of `x⁴ + a·x³ + b·x² + c·x + d` using Ferrari's method, refined with Newton's
+/// method since the closed form loses precision
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    // Substitute x = y - a/4 to get y⁴ + p·y² + q·y + r
+    let p = b - 3.0 * a * a / 8.0;
+    let q = c - a * b / 2.0 + a.powi(3) / 8.0;
+    let r = d - a * c / 4.0 + a * a * b / 16.0 - 3.0 * a.powi(4) / 256.0;
+
+    let mut roots = Vec::new();
+    if q.abs() < 1e-10 {
+        // Quadratic in y²
+        for y_squared in solve_quadratic(p, r) {
+            if y_squared >= 0.0 {
+                roots.extend([y_squared.sqrt(), -y_squared.sqrt()]);
+            }
+        }
+    } else {
+        // Any positive root m of the resolvent cubic splits the quartic into two quadratics
+        let Some(m) = solve_cubic(p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .filter(|&m| m > 0.0)
+            .max_by(|a, b| a.total_cmp(b))
+        else {
+            return roots;
+        };
+        let s = (2.0 * m).sqrt();
+        roots.extend(solve_quadratic(-s, p / 2.0 + m + s * q / (4.0 * m)));
+        roots.extend(solve_quadratic(s, p / 2.0 + m - s * q / (4.0 * m)));
+    }
+
+    roots
+        .into_iter()
+        .map(|y| {
+            let mut x = y - a / 4.0;
+            for _ in 0..2 {
+                let value = (((x + a) * x + b) * x + c) * x + d;
+                let slope = ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+                if slope.abs() > 1e-12 {
+                    x -= value / slope;
+                }
+            }
+            x
+        })
+        .collect()
+}
+
+/// Real roots of `x³ + a·x² + b·x + c`
+pub fn solve_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Substitute x = y - a/3 to get y³ + p·y + q
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a.powi(3) / 27.0 - a * b / 3.0 + c;
+    let shift = -a / 3.0;
+    let discriminant = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+
+    if discriminant > 0.0 {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(-q / 2.0 + sqrt_discriminant).cbrt() + (-q / 2.0 - sqrt_discriminant).cbrt() + shift]
+    } else {
+        // Three real roots
+        let radius = 2.0 * (-p / 3.0).max(0.0).sqrt();
+        let angle = if radius == 0.0 {
+            0.0
+        } else {
+            (3.0 * q / (p * radius)).clamp(-1.0, 1.0).acos() / 3.0
+        };
+        (0..3)
+            .map(|k| radius * (angle - 2.0 * PI * k as f64 / 3.0).cos() + shift)
+            .collect()
+    }
+}
+
+/// Real roots of `x² + b·x + c`
+fn solve_quadratic(b: f64, c: f64) -> Vec<f64> {
+    let discriminant = b * b - 4.0 * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    let sqrt_discriminant = discriminant.sqrt();
+    vec![
+        (-b - sqrt_discriminant) / 2.0,
+        (-b + sqrt_discriminant) / 2.0,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::discriminant;
+
+    fn sorted(mut roots: Vec<f64>) -> Vec<f64> {
+        roots.sort_by(|a, b| a.total_cmp(b));
+        roots
+    }
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64]) {
+        let roots = sorted(roots);
+        assert_eq!(roots.len(), expected.len(), "{roots:?}");
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < 1e-9, "{roots:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn discriminant_is_none_below_zero() {
+        assert_eq!(discriminant(1.0, 4.0, 3.0), Some(4.0));
+        assert_eq!(discriminant(1.0, 2.0, 1.0), Some(0.0));
+        assert_eq!(discriminant(1.0, 1.0, 1.0), None);
+    }
+
+    #[test]
+    fn quadratic_roots() {
+        // (x - 1)(x + 3)
+        assert_roots(solve_quadratic(2.0, -3.0), &[-3.0, 1.0]);
+        assert_roots(solve_quadratic(0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn cubic_with_one_real_root() {
+        // (x - 2)(x² + 1)
+        assert_roots(solve_cubic(-2.0, 1.0, -2.0), &[2.0]);
+    }
+
+    #[test]
+    fn cubic_with_three_real_roots() {
+        // (x + 1)(x - 2)(x - 4)
+        assert_roots(solve_cubic(-5.0, 2.0, 8.0), &[-1.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn quartic_with_four_real_roots() {
+        // (x + 2)(x + 1)(x - 1)(x - 3)
+        assert_roots(solve_quartic(-1.0, -7.0, 1.0, 6.0), &[-2.0, -1.0, 1.0, 3.0]);
+    }
+
+    #[test]
+    fn biquadratic_quartic() {
+        // (x² - 1)(x² - 4), where the cubic term of the depressed quartic vanishes
+        assert_roots(solve_quartic(0.0, -5.0, 0.0, 4.0), &[-2.0, -1.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn quartic_without_real_roots() {
+        // (x² + 1)(x² + 2x + 5)
+        assert_roots(solve_quartic(2.0, 6.0, 2.0, 5.0), &[]);
+    }
+
+    #[test]
+    fn ray_through_the_hole_misses() {
+        let torus = Torus::new(Point::zeros(), 2.0, 0.5, Texture::Reflective);
+        let ray = Ray::new(Point::new(0.0, 5.0, 0.0), Point::new(0.0, -1.0, 0.0), 0);
+        assert!(torus.intersection(&ray).is_none());
+
+        let ray = Ray::new(Point::new(2.0, 5.0, 0.0), Point::new(0.0, -1.0, 0.0), 0);
+        let distance = torus.intersection(&ray).unwrap().distance;
+        assert!((distance - 4.5).abs() < 1e-6, "{distance}");
+    }
+}
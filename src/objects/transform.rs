@@ -0,0 +1,117 @@
+use crate::objects::Intersection;
+use crate::raytracer::Ray;
+use crate::type_aliases::{Direction, Normal, Point};
+use nalgebra::{Matrix4, Rotation3, Unit};
+
+/// `Transform` moves, rotates and scales objects. The inverse is kept alongside the matrix since
+/// rays are transformed into the space of the object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub matrix: Matrix4<f64>,
+    pub inverse: Matrix4<f64>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            matrix: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+        }
+    }
+
+    pub fn translation(offset: Point) -> Self {
+        Self {
+            matrix: Matrix4::new_translation(&offset),
+            inverse: Matrix4::new_translation(&-offset),
+        }
+    }
+
+    /// Rotation around `axis` by `degrees`, counterclockwise when looking against the axis
+    pub fn rotation(axis: Direction, degrees: f64) -> Self {
+        let rotation = Rotation3::from_axis_angle(&Unit::new_normalize(axis), degrees.to_radians());
+        Self {
+            matrix: rotation.to_homogeneous(),
+            inverse: rotation.inverse().to_homogeneous(),
+        }
+    }
+
+    /// Scale along each axis. Scaling by zero flattens the object and can not be undone.
+    pub fn scale(factors: Point) -> Self {
+        Self {
+            matrix: Matrix4::new_nonuniform_scaling(&factors),
+            inverse: Matrix4::new_nonuniform_scaling(&factors.map(|factor| 1.0 / factor)),
+        }
+    }
+
+    /// Scale first, then rotate around the X, Y and Z axes by the angles in degrees, then move
+    /// to `position`
+    pub fn from_parts(position: Point, rotation: Point, scale: Point) -> Self {
+        Self::scale(scale)
+            .then(&Self::rotation(Direction::new(1.0, 0.0, 0.0), rotation.x))
+            .then(&Self::rotation(Direction::new(0.0, 1.0, 0.0), rotation.y))
+            .then(&Self::rotation(Direction::new(0.0, 0.0, 1.0), rotation.z))
+            .then(&Self::translation(position))
+    }
+
+    /// `self` followed by `other`
+    pub fn then(&self, other: &Transform) -> Self {
+        Self {
+            matrix: other.matrix * self.matrix,
+            inverse: self.inverse * other.inverse,
+        }
+    }
+
+    pub fn transform_point(&self, point: Point) -> Point {
+        self.matrix.transform_point(&point.into()).coords
+    }
+
+    pub fn transform_vector(&self, vector: Direction) -> Direction {
+        self.matrix.transform_vector(&vector)
+    }
+
+    /// Normals stay perpendicular to the surface by using the inverse transpose
+    pub fn transform_normal(&self, normal: Normal) -> Normal {
+        (self.inverse.fixed_view::<3, 3>(0, 0).transpose() * normal).normalize()
+    }
+
+    pub fn inverse_point(&self, point: Point) -> Point {
+        self.inverse.transform_point(&point.into()).coords
+    }
+
+    pub fn inverse_vector(&self, vector: Direction) -> Direction {
+        self.inverse.transform_vector(&vector)
+    }
+
+    /// The ray in the space before the transform, and how much longer distances are in that
+    /// space
+    pub fn ray_to_local(&self, ray: &Ray) -> (Ray, f64) {
+        let direction = self.inverse_vector(ray.direction);
+        let stretch = direction.norm();
+
+        let mut local_ray =
+            Ray::new(self.inverse_point(ray.origin), direction, ray.depth).with_time(ray.time);
+        local_ray.intersection_dist = ray.intersection_dist * stretch;
+        local_ray.sample = ray.sample;
+        (local_ray, stretch)
+    }
+
+    /// Move an intersection found with `ray_to_local` back into the scene
+    pub fn intersection_to_world(
+        &self,
+        mut intersection: Intersection,
+        stretch: f64,
+    ) -> Intersection {
+        intersection.hit_point = self.transform_point(intersection.hit_point);
+        intersection.normal = self.transform_normal(intersection.normal);
+        intersection.tangent = self.transform_vector(intersection.tangent).normalize();
+        intersection.bitangent = self.transform_vector(intersection.bitangent).normalize();
+        intersection.distance /= stretch;
+        intersection
+    }
+}
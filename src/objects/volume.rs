@@ -0,0 +1,49 @@
+use crate::objects::{Intersection, Object};
+use crate::raytracer::Ray;
+use crate::textures::{Medium, Texture};
+use std::sync::Arc;
+
+/// `Volume` fills the inside of a convex `boundary` object with a participating medium, e.g. a
+/// sphere filled with smoke.
+///
+/// Rays entering the volume scatter at a random distance inside of it, or pass straight through
+/// if they leave the boundary first. The boundary's own texture is not used.
+pub struct Volume {
+    pub boundary: Arc<dyn Object>,
+    pub medium: Medium,
+}
+
+impl Volume {
+    pub fn new(boundary: Arc<dyn Object>, medium: Medium) -> Self {
+        Self { boundary, medium }
+    }
+}
+
+impl Object for Volume {
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        // Find where the ray enters and leaves the boundary. A ray starting inside enters at
+        // 0.0, and one that only touches the boundary does not get through it.
+        let span = self
+            .boundary
+            .spans(ray)
+            .into_iter()
+            .find(|span| span.exit.distance > 1e-6)?;
+        let (enter, exit) = (span.enter.distance.max(0.0), span.exit.distance);
+
+        let distance = enter + self.medium.sample_distance(ray.sample);
+        if distance >= exit || distance >= ray.intersection_dist {
+            return None;
+        }
+
+        Some(Intersection::new(
+            ray.origin + ray.direction * distance,
+            -ray.direction,
+            distance,
+            self.texture(),
+        ))
+    }
+
+    fn texture(&self) -> Texture {
+        Texture::Volume(self.medium)
+    }
+}
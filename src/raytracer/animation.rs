@@ -0,0 +1,331 @@
+use crate::config::camera::DEFAULT_CHECKPOINT_INTERVAL;
+use crate::objects::{Instance, Object, Transform};
+use crate::raytracer::{CameraBuilder, Scene};
+use crate::type_aliases::Point;
+use std::ops::{Add, Mul, RangeInclusive, Sub};
+use std::path::Path;
+use std::sync::Arc;
+
+/// How values change between two keyframes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Interpolation {
+    /// Constant speed from one keyframe to the next
+    #[default]
+    Linear,
+    /// Speeding up after a keyframe and slowing down before the next, stopping at every keyframe
+    SmoothStep,
+    /// A smooth curve through all keyframes without stopping, good for camera paths
+    CatmullRom,
+}
+
+/// Anything that can be blended between keyframes, like `f64` and `Point`
+pub trait Interpolate:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f64, Output = Self>
+{
+}
+
+impl<T> Interpolate for T where T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T> {}
+
+/// `Track` is a value changing over time, given by keyframes in seconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track<T> {
+    pub keyframes: Vec<(f64, T)>,
+    pub interpolation: Interpolation,
+}
+
+impl<T: Interpolate> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            keyframes: Vec::new(),
+            interpolation,
+        }
+    }
+
+    pub fn with_key(mut self, time: f64, value: T) -> Self {
+        let index = self
+            .keyframes
+            .partition_point(|(key_time, _)| *key_time <= time);
+        self.keyframes.insert(index, (time, value));
+        self
+    }
+
+    /// The value at `time`, or `None` if the track has no keyframes. Before the first and after
+    /// the last keyframe the value stays the same.
+    pub fn value_at(&self, time: f64) -> Option<T> {
+        let keys = &self.keyframes;
+        let next = keys.partition_point(|(key_time, _)| *key_time <= time);
+        if next == 0 {
+            return keys.first().map(|(_, value)| *value);
+        }
+        if next == keys.len() {
+            return keys.last().map(|(_, value)| *value);
+        }
+
+        let (start_time, start) = keys[next - 1];
+        let (end_time, end) = keys[next];
+        let t = (time - start_time) / (end_time - start_time);
+
+        Some(match self.interpolation {
+            Interpolation::Linear => start + (end - start) * t,
+            Interpolation::SmoothStep => start + (end - start) * (t * t * (3.0 - 2.0 * t)),
+            Interpolation::CatmullRom => {
+                // The first and last keyframes are repeated for the missing neighbours
+                let before = keys[next.saturating_sub(2)].1;
+                let after = keys[(next + 1).min(keys.len() - 1)].1;
+                catmull_rom(before, start, end, after, t)
+            }
+        })
+    }
+}
+
+impl<T: Interpolate> Default for Track<T> {
+    fn default() -> Self {
+        Self::new(Interpolation::default())
+    }
+}
+
+fn catmull_rom<T: Interpolate>(p0: T, p1: T, p2: T, p3: T, t: f64) -> T {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+/// Keyframes for the camera. Empty tracks leave the setting of the `CameraBuilder` as it is.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CameraTracks {
+    pub position: Track<Point>,
+    pub look_at: Track<Point>,
+    pub focal_length: Track<f64>,
+}
+
+impl CameraTracks {
+    pub fn apply(&self, builder: &mut CameraBuilder, time: f64) {
+        if let Some(position) = self.position.value_at(time) {
+            builder.position_by_coordinates(position);
+        }
+        if let Some(look_at) = self.look_at.value_at(time) {
+            builder.look_at(look_at);
+        }
+        if let Some(focal_length) = self.focal_length.value_at(time) {
+            builder.focal_length(focal_length);
+        }
+    }
+}
+
+/// Keyframes for the position, rotation in degrees around the X, Y and Z axes, and scale of an
+/// object or group. Empty tracks keep the object where it is.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TransformTracks {
+    pub position: Track<Point>,
+    pub rotation: Track<Point>,
+    pub scale: Track<Point>,
+}
+
+impl TransformTracks {
+    pub fn transform_at(&self, time: f64) -> Transform {
+        Transform::from_parts(
+            self.position.value_at(time).unwrap_or_default(),
+            self.rotation.value_at(time).unwrap_or_default(),
+            self.scale
+                .value_at(time)
+                .unwrap_or_else(|| Point::new(1.0, 1.0, 1.0)),
+        )
+    }
+}
+
+/// `Animation` is a timeline of keyframes for the camera, objects and groups.
+///
+/// Animated objects are added to the scene of every frame by an `Instance`, and animated groups
+/// found by name get their transform replaced.
+pub struct Animation {
+    pub frames_per_second: f64,
+    pub camera: CameraTracks,
+    pub objects: Vec<(Arc<dyn Object>, TransformTracks)>,
+    pub groups: Vec<(String, TransformTracks)>,
+}
+
+/// Which frames `Animation::render` renders and where to
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceOptions {
+    /// Folder for `frame_0001.png`, `frame_0002.png` and so on
+    pub directory: String,
+    /// Frame numbers, starting at 1
+    pub frames: RangeInclusive<u32>,
+    /// Skip frames that were already saved, and continue a frame that was interrupted from its
+    /// checkpoint
+    pub resume: bool,
+}
+
+impl Animation {
+    pub fn new(frames_per_second: f64) -> Self {
+        Self {
+            frames_per_second,
+            camera: CameraTracks::default(),
+            objects: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    pub fn with_camera(mut self, camera: CameraTracks) -> Self {
+        self.camera = camera;
+        self
+    }
+
+    pub fn with_object(mut self, object: Arc<dyn Object>, tracks: TransformTracks) -> Self {
+        self.objects.push((object, tracks));
+        self
+    }
+
+    pub fn with_group(mut self, name: impl Into<String>, tracks: TransformTracks) -> Self {
+        self.groups.push((name.into(), tracks));
+        self
+    }
+
+    /// Time of the start of frame `frame`, the first frame being 1
+    pub fn frame_time(&self, frame: u32) -> f64 {
+        frame.saturating_sub(1) as f64 / self.frames_per_second
+    }
+
+    /// `scene` with every animated object and group where it is at `time`
+    pub fn scene_at(&self, scene: &Scene, time: f64) -> Scene {
+        let mut scene = scene.clone();
+        for (object, tracks) in &self.objects {
+            let transform = tracks.transform_at(time);
+            scene
+                .objects
+                .push(Arc::new(Instance::new(object.clone(), transform)));
+        }
+        for (name, tracks) in &self.groups {
+            match scene.group_mut(name) {
+                Some(group) => group.transform = tracks.transform_at(time),
+                None => eprintln!("Animation: no group called {name}"),
+            }
+        }
+        scene
+    }
+
+    /// Render the frames in `options` one by one to numbered images.
+    ///
+    /// `builder` sets up the camera for every frame before the camera tracks are applied. The
+    /// shutter interval is moved to the time of each frame, so `Moving` objects keep moving
+    /// through the animation. Every frame is checkpointed next to its image while it renders.
+    pub fn render(&self, builder: &CameraBuilder, scene: &Scene, options: &SequenceOptions) {
+        if let Err(err) = std::fs::create_dir_all(&options.directory) {
+            eprintln!("Failed to create {}: {err}", options.directory);
+            return;
+        }
+
+        for frame in options.frames.clone() {
+            let path = format!("{}/frame_{frame:04}.png", options.directory);
+            let checkpoint_path = format!("{}/frame_{frame:04}.ckpt", options.directory);
+            if options.resume && Path::new(&path).exists() {
+                println!("Skipping frame {frame}, {path} already exists");
+                continue;
+            }
+
+            let time = self.frame_time(frame);
+            let mut frame_builder = builder.clone();
+            self.camera.apply(&mut frame_builder, time);
+            // Without motion blur the shutter still has to be at the frame, or every frame would
+            // see `Moving` objects where they are at time 0.0
+            if let Some((open, close)) = builder.shutter {
+                frame_builder.shutter(open + time, close + time);
+            } else {
+                frame_builder.shutter(time, time);
+            }
+            frame_builder.checkpoint(
+                &checkpoint_path,
+                builder
+                    .checkpoint_interval
+                    .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL),
+            );
+
+            let mut camera = frame_builder.build();
+            if options.resume && Path::new(&checkpoint_path).exists() {
+                if let Err(err) = camera.resume_from_checkpoint(&checkpoint_path) {
+                    eprintln!("Failed to resume frame {frame} from {checkpoint_path}: {err}");
+                }
+            }
+
+            camera.send_rays(Arc::new(self.scene_at(scene, time)));
+            if let Err(err) = camera.write_to_image(&path) {
+                eprintln!("Failed to write {path}: {err}");
+                continue;
+            }
+            // The frame is done, so the checkpoint is not needed anymore
+            let _ = std::fs::remove_file(&checkpoint_path);
+            println!("Rendered frame {frame} to {path}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(interpolation: Interpolation) -> Track<f64> {
+        // Added out of order on purpose, `with_key` sorts them
+        Track::new(interpolation)
+            .with_key(2.0, 4.0)
+            .with_key(0.0, 0.0)
+            .with_key(1.0, 2.0)
+    }
+
+    #[test]
+    fn empty_track_has_no_value() {
+        assert_eq!(Track::<f64>::default().value_at(1.0), None);
+    }
+
+    #[test]
+    fn values_are_held_outside_the_keyframes() {
+        let track = track(Interpolation::Linear);
+        assert_eq!(track.value_at(-1.0), Some(0.0));
+        assert_eq!(track.value_at(5.0), Some(4.0));
+    }
+
+    #[test]
+    fn linear_interpolation() {
+        let track = track(Interpolation::Linear);
+        assert_eq!(track.value_at(0.25), Some(0.5));
+        assert_eq!(track.value_at(1.0), Some(2.0));
+        assert_eq!(track.value_at(1.5), Some(3.0));
+    }
+
+    #[test]
+    fn smooth_step_eases_in_and_out() {
+        let track = track(Interpolation::SmoothStep);
+        assert_eq!(track.value_at(0.5), Some(1.0));
+        assert!(track.value_at(0.1).unwrap() < 0.2);
+        assert!(track.value_at(0.9).unwrap() > 1.8);
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_every_keyframe() {
+        let track = Track::new(Interpolation::CatmullRom)
+            .with_key(0.0, 0.0)
+            .with_key(1.0, 3.0)
+            .with_key(2.0, 1.0)
+            .with_key(3.0, 5.0);
+        for (time, value) in [(0.0, 0.0), (1.0, 3.0), (2.0, 1.0), (3.0, 5.0)] {
+            let at = track.value_at(time).unwrap();
+            assert!((at - value).abs() < 1e-12, "{at} at {time}");
+        }
+        // Evenly spaced keyframes on a line stay on it
+        let line = (0..4).fold(Track::new(Interpolation::CatmullRom), |track, key| {
+            track.with_key(key as f64, key as f64 * 2.0)
+        });
+        assert!((line.value_at(1.25).unwrap() - 2.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn frames_start_at_one() {
+        let animation = Animation::new(24.0);
+        assert_eq!(animation.frame_time(1), 0.0);
+        assert_eq!(animation.frame_time(25), 1.0);
+        assert_eq!(animation.frame_time(0), 0.0);
+    }
+}
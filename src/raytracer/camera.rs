@@ -1,60 +1,363 @@
 use crate::config::camera::*;
-use crate::type_aliases::{Color, Direction};
+use crate::objects::Motion;
+use crate::textures::Image;
+use crate::type_aliases::{Color, Direction, Directions};
+
+/// How fisheye lenses map angles from the center of view onto the image circle
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FisheyeMapping {
+    /// The distance from the center grows evenly with the angle, common for dome renders
+    #[default]
+    Equidistant,
+    /// Every part of the image covers the same solid angle, like most real fisheye lenses
+    Equisolid,
+}
+
+/// How rays are sent out of the camera
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Projection {
+    /// A pinhole camera, things further away look smaller
+    #[default]
+    Perspective,
+    /// Parallel rays for technical views. `height` is the height of the view in scene units.
+    Orthographic { height: f64 },
+    /// A circular image covering `field_of_view` degrees, which can be more than 180
+    Fisheye {
+        mapping: FisheyeMapping,
+        field_of_view: f64,
+    },
+    /// A 360 by 180 degree panorama, e.g. for VR. Use an aspect ratio of 2:1.
+    Equirectangular,
+}
+
+/// A field of view in degrees, measured along one side of the image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldOfView {
+    Vertical(f64),
+    Horizontal(f64),
+}
+
+/// `Exposure` sets how bright the image is like the settings of a real camera. Doubling the ISO
+/// or the shutter time, or opening the aperture by one f-stop, doubles the brightness.
+///
+/// The default, ISO 100 at 1/60 s and f/4, leaves the image as it is. The shutter time here
+/// only changes the brightness, motion blur is set with `CameraBuilder::shutter`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exposure {
+    pub iso: f64,
+    /// Seconds
+    pub shutter_speed: f64,
+    pub f_stop: f64,
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Self {
+            iso: 100.0,
+            shutter_speed: 1.0 / 60.0,
+            f_stop: 4.0,
+        }
+    }
+}
+
+impl Exposure {
+    pub fn new(iso: f64, shutter_speed: f64, f_stop: f64) -> Self {
+        Self {
+            iso,
+            shutter_speed,
+            f_stop,
+        }
+    }
+
+    /// How much brighter than the default exposure this is
+    pub fn multiplier(&self) -> f64 {
+        let reference = Self::default();
+        (self.iso / reference.iso) * (self.shutter_speed / reference.shutter_speed)
+            / (self.f_stop / reference.f_stop).powi(2)
+    }
+
+    /// The exposure value at ISO 100. Every step up halves the brightness.
+    pub fn exposure_value(&self) -> f64 {
+        (self.f_stop * self.f_stop / self.shutter_speed).log2() - (self.iso / 100.0).log2()
+    }
+}
 
 #[derive(Debug)]
 pub struct Camera {
     pub sample_size: u16,
     pub position: Vector3<f64>,
     pub look_at: Vector3<f64>,
+    /// Which way is up in the image. Only the part at a right angle to the view direction is
+    /// used, so it does not have to be exact.
     pub up_direction: Vector3<f64>,
+    /// Degrees to turn the camera counterclockwise around the view direction
+    pub roll: f64,
     pub resolution: Resolution,
     pub aspect_ratio: f64,
+    /// Distance from the pinhole to an image one unit high. See `focal_length_mm` and
+    /// `vertical_field_of_view` for the same in physical units.
     pub focal_length: f64,
+    /// Sensor width in millimetres
     pub sensor_width: f64,
+    pub exposure: Exposure,
+    pub projection: Projection,
+    /// Moves the perspective image sideways by this many image heights, like a shift lens,
+    /// without turning the camera
+    pub lens_shift: f64,
+    /// How far the eye is to the right of `position` for stereo equirectangular panoramas,
+    /// negative for the left eye. The eye turns with every ray, so the stereo effect works in
+    /// all directions.
+    pub eye_offset: f64,
+    pub tile_size: u32,
+    pub tile_order: TileOrder,
+    /// Only render the pixels in this rectangle, e.g. to crop the image. The other pixels keep
+    /// their samples, or stay black in a new render.
+    pub region: Option<Tile>,
+    /// Rays are sent at random times between the shutter opening and closing. Anything moving
+    /// in that time is blurred.
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+    /// Moves the camera, together with the point it looks at, while the shutter is open
+    pub motion: Option<Motion>,
+    pub checkpoint_path: Option<String>,
+    pub checkpoint_interval: Duration,
+    pub render_state: RenderState,
     pub pixels: Pixels,
 }
 
 impl Camera {
     pub fn send_rays(&mut self, scene: Arc<Scene>) {
+        self.send_rays_with_callback(scene, |_, _| {});
+    }
+
+    /// Render the image tile by tile and call `on_tile` with the pixels of every finished tile.
+    ///
+    /// Tiles are handed out to the rayon workers in `tile_order`, and `on_tile` is called from
+    /// the worker thread as soon as a tile is done, so front ends can draw it right away.
+    ///
+    /// Every pixel is sampled until it has `sample_size` samples in `render_state`, so a resumed
+    /// render only adds the missing samples. If `checkpoint_path` is set, the render state is
+    /// written there every `checkpoint_interval` and once more when the render is finished.
+    pub fn send_rays_with_callback<F>(&mut self, scene: Arc<Scene>, on_tile: F)
+    where
+        F: Fn(&Tile, &[Color]) + Sync,
+    {
+        if self.render_state.resolution != self.resolution {
+            self.render_state = RenderState::new(self.resolution, self.render_state.seed);
+        }
+
+        let width = self.resolution.0;
+        let exposure = self.exposure.multiplier();
+        let tiles = self.tiles();
+        let state = Mutex::new((std::mem::take(&mut self.render_state), Instant::now()));
+
+        // `par_bridge` pulls the tiles in order, so the workers follow the tile order
+        tiles.into_iter().par_bridge().for_each(|tile| {
+            let indices: Vec<usize> = tile
+                .coordinates()
+                .map(|(column, row)| (row * width + column) as usize)
+                .collect();
+
+            let (seed, counts) = {
+                let (render_state, _) = &*state.lock().unwrap();
+                let counts: Vec<u32> = indices
+                    .iter()
+                    .map(|&index| render_state.sample_counts[index])
+                    .collect();
+                (render_state.seed, counts)
+            };
+
+            let samples = self.render_tile(&tile, seed, &counts, &scene);
+
+            let mut guard = state.lock().unwrap();
+            let (render_state, last_checkpoint) = &mut *guard;
+            let mut pixels = Pixels::with_capacity(indices.len());
+            for (&index, (color, count)) in indices.iter().zip(samples) {
+                render_state.accumulated[index] += color;
+                render_state.sample_counts[index] += count;
+                pixels.push(
+                    render_state.accumulated[index] * exposure
+                        / render_state.sample_counts[index].max(1) as f64,
+                );
+            }
+
+            // Copy the state while it is locked and write it afterwards, so the other workers
+            // do not wait for the disk
+            let checkpoint = match &self.checkpoint_path {
+                Some(path) if last_checkpoint.elapsed() >= self.checkpoint_interval => {
+                    *last_checkpoint = Instant::now();
+                    Some((render_state.clone(), path))
+                }
+                _ => None,
+            };
+            drop(guard);
+
+            if let Some((render_state, path)) = checkpoint {
+                write_checkpoint(&render_state, path);
+            }
+
+            on_tile(&tile, &pixels);
+        });
+
+        // Update the camera's pixels
+        self.render_state = state.into_inner().unwrap().0;
+        self.pixels = self.exposed_pixels(&self.render_state);
+
+        if let Some(path) = &self.checkpoint_path {
+            write_checkpoint(&self.render_state, path);
+        }
+    }
+
+    /// Render the pixels in `region` again from scratch and keep the rest of the image, e.g. to
+    /// look at a detail after changing the scene. The projection stays that of the whole image.
+    pub fn send_rays_in_region(&mut self, scene: Arc<Scene>, region: Tile) {
         let (width, height) = self.resolution;
-        let total_pixels = (width * height) as usize;
+        let Some(region) = region.clip(&Tile {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }) else {
+            return;
+        };
+
+        if self.render_state.resolution == self.resolution {
+            for (column, row) in region.coordinates() {
+                let index = (row * width + column) as usize;
+                self.render_state.accumulated[index] = Color::default();
+                self.render_state.sample_counts[index] = 0;
+            }
+        }
 
-        // Pre-allocate a vector with default Color values
-        let mut colors = vec![Vector3::default(); total_pixels];
+        let previous_region = self.region.replace(region);
+        self.send_rays(scene);
+        self.region = previous_region;
+    }
 
-        // Parallelize the computation for each pixel
-        colors
-            .par_iter_mut()
-            .enumerate()
-            .for_each(|(pixel, pixel_color)| {
-                let column = pixel as u32 % width;
-                let row = (total_pixels - pixel) as u32 / width;
-                let mut total_color = Color::black();
+    /// The tiles `send_rays` renders, in order. Only the parts inside `region` if it is set.
+    pub fn tiles(&self) -> Vec<Tile> {
+        let tiles = create_tiles(self.resolution, self.tile_size, self.tile_order);
+        match &self.region {
+            Some(region) => tiles.iter().filter_map(|tile| tile.clip(region)).collect(),
+            None => tiles,
+        }
+    }
 
-                for _sample in 0..self.sample_size {
-                    let direction = self.ray_direction(column, row);
-                    let mut ray = Ray::new(self.position, direction, 0);
+    /// Continue from the render state stored at `path`.
+    ///
+    /// The next `send_rays` only adds the samples that are missing to reach `sample_size`, so
+    /// raising `sample_size` refines an already finished image.
+    pub fn resume_from_checkpoint(&mut self, path: &str) -> std::io::Result<()> {
+        let render_state = RenderState::read_from_file(path)?;
+        if render_state.resolution != self.resolution {
+            let (w, h) = render_state.resolution;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("checkpoint resolution {w}x{h} does not match the camera"),
+            ));
+        }
 
-                    ray.trace(&scene); // Recursive ray tracing with default 50 depth.
+        self.pixels = self.exposed_pixels(&render_state);
+        self.render_state = render_state;
+        Ok(())
+    }
 
-                    if ray.collisions.is_empty() {
-                        total_color += scene.background(); // No collision, add background color.
-                        continue;
-                    }
+    /// The average of the samples in `render_state` at the camera's exposure. The samples are
+    /// stored unexposed, so a checkpoint can be resumed with a different exposure.
+    fn exposed_pixels(&self, render_state: &RenderState) -> Pixels {
+        let exposure = self.exposure.multiplier();
+        render_state
+            .average()
+            .into_iter()
+            .map(|color| color * exposure)
+            .collect()
+    }
 
-                    if ray.hit_light_source {
-                        total_color += ray.average_color(&scene);
-                    } else {
-                        total_color += ray.average_color(&scene) * scene.brightness
-                    }
-                }
+    /// Render the missing samples of a tile. Returns the sum of the new samples and how many
+    /// were taken for each pixel.
+    fn render_tile(
+        &self,
+        tile: &Tile,
+        seed: u64,
+        counts: &[u32],
+        scene: &Scene,
+    ) -> Vec<(Color, u32)> {
+        let (width, height) = self.resolution;
+        let total_pixels = width * height;
+
+        tile.coordinates()
+            .zip(counts)
+            .map(|((column, row), &count)| {
+                let pixel = row * width + column;
+                let samples = (self.sample_size as u32).saturating_sub(count);
+
+                // Seed per pixel and sample count, so resumed renders continue the sequence
+                let mut rng = StdRng::seed_from_u64(seed ^ ((pixel as u64) << 32 | count as u64));
+                let row = (total_pixels - pixel) / width;
+                let color = self.sample_pixel(column, row, samples, &mut rng, scene);
+                (color, samples)
+            })
+            .collect()
+    }
 
-                // Set the current pixel to the average color of the samples.
-                *pixel_color = total_color / self.sample_size as f64;
-            });
+    /// The sum of `samples` rays sent through the pixel.
+    fn sample_pixel(
+        &self,
+        column: u32,
+        row: u32,
+        samples: u32,
+        rng: &mut StdRng,
+        scene: &Scene,
+    ) -> Color {
+        let mut total_color = Color::black();
+
+        for _sample in 0..samples {
+            let time = self.sample_time(rng);
+            let Some((origin, direction)) = self.primary_ray(
+                column as f64 + rng.gen_range(0.0..1.0),
+                row as f64 + rng.gen_range(0.0..1.0),
+                self.position_at(time),
+            ) else {
+                continue; // Outside of the image, stays black
+            };
+            let mut ray = Ray::new(origin, direction, 0)
+                .with_time(time)
+                .with_sample(rng);
+
+            ray.trace(scene, rng); // Recursive ray tracing with default 50 depth.
+
+            if ray.collisions.is_empty() {
+                total_color += scene.background(); // No collision, add background color.
+                continue;
+            }
+
+            // Before `average_color`, which takes the light source off `collisions`
+            total_color += ray.direct_lighting();
+
+            if ray.hit_light_source {
+                total_color += ray.average_color(scene);
+            } else {
+                total_color += ray.average_color(scene) * scene.brightness
+            }
+        }
 
-        // Update the camera's pixels
-        self.pixels = colors;
+        total_color
+    }
+
+    /// A random moment while the shutter is open
+    fn sample_time(&self, rng: &mut StdRng) -> f64 {
+        if self.shutter_close > self.shutter_open {
+            rng.gen_range(self.shutter_open..self.shutter_close)
+        } else {
+            self.shutter_open
+        }
+    }
+
+    pub fn position_at(&self, time: f64) -> Point {
+        match &self.motion {
+            Some(motion) => self.position + motion.offset_at(time),
+            None => self.position,
+        }
     }
 
     pub fn write_to_ppm(&self, path: &str) {
@@ -84,33 +387,171 @@ impl Camera {
         }
     }
 
-    fn ray_direction(&self, pixel_x: u32, pixel_y: u32) -> Vector3<f64> {
-        // Calculate the camera basis vectors
-        let view_direction = (self.position - self.look_at).normalize();
-        let right_vector = self.up_direction.cross(&view_direction).normalize();
+    /// Save the image as PNG or any other format `Image::save` supports
+    pub fn write_to_image(&self, path: &str) -> std::io::Result<()> {
+        self.image().save(path)
+    }
+
+    /// The rendered pixels, gamma corrected
+    pub fn image(&self) -> Image {
+        let (width, height) = self.resolution;
+        Image {
+            width,
+            height,
+            pixels: self
+                .pixels
+                .iter()
+                .map(|pixel| pixel.correct_gamma(2.0))
+                .collect(),
+        }
+    }
+
+    /// Field of view from the bottom to the top of the image in degrees
+    pub fn vertical_field_of_view(&self) -> f64 {
+        2.0 * (0.5 / self.focal_length).atan().to_degrees()
+    }
+
+    /// Field of view from the left to the right of the image in degrees
+    pub fn horizontal_field_of_view(&self) -> f64 {
+        2.0 * (0.5 * self.aspect_ratio / self.focal_length)
+            .atan()
+            .to_degrees()
+    }
+
+    /// The focal length of a lens with the same field of view on a sensor `sensor_width`
+    /// millimetres wide
+    pub fn focal_length_mm(&self) -> f64 {
+        self.focal_length * self.sensor_width / self.aspect_ratio
+    }
+
+    /// The right, up and backwards directions of the camera.
+    ///
+    /// When looking straight along `up_direction`, e.g. straight down from above, up is
+    /// undefined, so the world axis furthest from the view direction is used instead.
+    pub fn basis(&self) -> (Direction, Direction, Direction) {
+        let view_direction = (self.position - self.look_at)
+            .try_normalize(1e-12)
+            .unwrap_or(Direction::new(0.0, 0.0, 1.0));
+
+        let right_vector = self
+            .up_direction
+            .cross(&view_direction)
+            .try_normalize(1e-9)
+            .unwrap_or_else(|| {
+                let axis = view_direction.abs().imin();
+                let fallback = Direction::ith(axis, 1.0);
+                fallback.cross(&view_direction).normalize()
+            });
         let up_vector = view_direction.cross(&right_vector);
+
+        let (sin, cos) = self.roll.to_radians().sin_cos();
+        (
+            right_vector * cos + up_vector * sin,
+            up_vector * cos - right_vector * sin,
+            view_direction,
+        )
+    }
+
+    /// The ray through the center of a pixel, in pixels from the top left like `Tile`, from the
+    /// camera position at the opening of the shutter. Used to find what is under the mouse.
+    pub fn pixel_ray(&self, column: u32, row: u32) -> Option<Ray> {
+        let (_, height) = self.resolution;
+        let time = self.shutter_open;
+        let (origin, direction) = self.primary_ray(
+            column as f64 + 0.5,
+            height as f64 - row as f64 - 0.5,
+            self.position_at(time),
+        )?;
+        Some(Ray::new(origin, direction, 0).with_time(time))
+    }
+
+    /// The ray through a point on the image, in pixels from the bottom left, starting from
+    /// `position`. Pixels outside of the image circle of a fisheye lens have no ray.
+    fn primary_ray(&self, x: f64, y: f64, position: Point) -> Option<(Point, Direction)> {
+        let (right_vector, up_vector, view_direction) = self.basis();
         let (width, height) = self.resolution;
-        let mut rand = rand::thread_rng();
 
         // Convert pixel coordinates to normalized world coordinates
-        let normalized_x = (pixel_x as f64 + rand.gen_range(0.0..1.0)) / (width as f64) - 0.5;
-        let normalized_y = (pixel_y as f64 + rand.gen_range(0.0..1.0)) / (height as f64) - 0.5;
+        let normalized_x = x / (width as f64) - 0.5;
+        let normalized_y = y / (height as f64) - 0.5;
+        let image_x = normalized_x * self.aspect_ratio;
+
+        match self.projection {
+            Projection::Perspective => Some((
+                position,
+                right_vector * (image_x + self.lens_shift) + up_vector * normalized_y
+                    - view_direction * self.focal_length,
+            )),
+            Projection::Orthographic { height } => Some((
+                position + (right_vector * image_x + up_vector * normalized_y) * height,
+                -view_direction,
+            )),
+            Projection::Fisheye {
+                mapping,
+                field_of_view,
+            } => {
+                // 1.0 on the edge of the image circle, which touches the top and bottom
+                let radius = 2.0 * image_x.hypot(normalized_y);
+                if radius > 1.0 {
+                    return None;
+                }
 
-        // Compute the ray direction
-        right_vector * (normalized_x * self.aspect_ratio) + up_vector * normalized_y
-            - view_direction * self.focal_length
+                let half_angle = field_of_view.to_radians() / 2.0;
+                let angle = match mapping {
+                    FisheyeMapping::Equidistant => radius * half_angle,
+                    FisheyeMapping::Equisolid => {
+                        2.0 * (radius * (half_angle / 2.0).sin()).clamp(-1.0, 1.0).asin()
+                    }
+                };
+                let sideways = (right_vector * image_x + up_vector * normalized_y)
+                    .try_normalize(1e-12)
+                    .unwrap_or_else(Direction::zeros);
+                Some((
+                    position,
+                    sideways * angle.sin() - view_direction * angle.cos(),
+                ))
+            }
+            Projection::Equirectangular => {
+                let longitude = normalized_x * std::f64::consts::TAU;
+                let latitude = normalized_y * std::f64::consts::PI;
+                // Omni-directional stereo: the eye circles around `position` with the direction
+                let eye = (right_vector * longitude.cos() + view_direction * longitude.sin())
+                    * self.eye_offset;
+                Some((
+                    position + eye,
+                    (right_vector * longitude.sin() - view_direction * longitude.cos())
+                        * latitude.cos()
+                        + up_vector * latitude.sin(),
+                ))
+            }
+        }
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct CameraBuilder {
     pub sample_size: Option<u16>,
     pub position: Option<Vector3<f64>>,
     pub look_at: Option<Vector3<f64>>,
     pub up_direction: Option<Vector3<f64>>,
+    pub roll: Option<f64>,
     pub resolution: Option<Resolution>,
     pub focal_length: Option<f64>,
+    pub focal_length_mm: Option<f64>,
+    pub field_of_view: Option<FieldOfView>,
     pub sensor_width: Option<f64>,
+    pub exposure: Option<Exposure>,
+    pub lens_shift: Option<f64>,
+    pub eye_offset: Option<f64>,
+    pub projection: Option<Projection>,
+    pub tile_size: Option<u32>,
+    pub tile_order: Option<TileOrder>,
+    pub region: Option<Tile>,
+    pub shutter: Option<(f64, f64)>,
+    pub motion: Option<Motion>,
+    pub checkpoint_path: Option<String>,
+    pub checkpoint_interval: Option<Duration>,
+    pub seed: Option<u64>,
 }
 
 impl CameraBuilder {
@@ -120,9 +561,24 @@ impl CameraBuilder {
             position: None,
             look_at: None,
             up_direction: None,
+            roll: None,
             resolution: None,
             focal_length: None,
+            focal_length_mm: None,
+            field_of_view: None,
             sensor_width: None,
+            exposure: None,
+            lens_shift: None,
+            eye_offset: None,
+            projection: None,
+            tile_size: None,
+            tile_order: None,
+            region: None,
+            shutter: None,
+            motion: None,
+            checkpoint_path: None,
+            checkpoint_interval: None,
+            seed: None,
         }
     }
 
@@ -133,43 +589,34 @@ impl CameraBuilder {
             sample_size: self.sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE),
             position: self.position.unwrap_or(DEFAULT_CAMERA_POSITION),
             look_at: self.look_at.unwrap_or_default(), // 0,0,0 is the default
-            up_direction: self.adjusted_up_direction(),
+            up_direction: self.up_direction.unwrap_or(Direction::up()),
+            roll: self.roll.unwrap_or(0.0),
             resolution: self.resolution.unwrap_or(DEFAULT_RESOLUTION),
             aspect_ratio: width as f64 / height as f64,
-            focal_length: self.focal_length.unwrap_or(DEFAULT_FOCAL_LENGTH),
+            focal_length: self.image_focal_length(width as f64 / height as f64),
             sensor_width: self.sensor_width.unwrap_or(DEFAULT_SENSOR_WIDTH),
+            exposure: self.exposure.unwrap_or_default(),
+            lens_shift: self.lens_shift.unwrap_or(0.0),
+            eye_offset: self.eye_offset.unwrap_or(0.0),
+            projection: self.projection.unwrap_or_default(),
+            tile_size: self.tile_size.unwrap_or(DEFAULT_TILE_SIZE),
+            tile_order: self.tile_order.unwrap_or_default(),
+            region: self.region,
+            shutter_open: self.shutter.map_or(0.0, |(open, _)| open),
+            shutter_close: self.shutter.map_or(0.0, |(_, close)| close),
+            motion: self.motion.clone(),
+            checkpoint_path: self.checkpoint_path.clone(),
+            checkpoint_interval: self
+                .checkpoint_interval
+                .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL),
+            render_state: RenderState::new(
+                self.resolution.unwrap_or(DEFAULT_RESOLUTION),
+                self.seed.unwrap_or_else(|| rand::thread_rng().gen()),
+            ),
             pixels: Vec::new(),
         }
     }
 
-    fn adjusted_up_direction(&self) -> Direction {
-        let mut camera_position = self.position.unwrap_or(DEFAULT_CAMERA_POSITION);
-
-        if camera_position.x == 0.0 && camera_position.z == 0.0 {
-            camera_position.z = 0.1;
-        }
-
-        let look_at_position = self.look_at.unwrap_or_default();
-
-        // Step 1: Compute Look Direction
-        let look_direction = look_at_position - camera_position;
-
-        // Step 2: Normalize the Look Direction
-        let normalized_look_direction = look_direction.normalize();
-
-        // Step 3: Define Up Direction (Positive Y)
-        let up_direction = -Vector3::y().normalize();
-
-        // Step 4: Compute Right Direction
-        let right_direction = normalized_look_direction.cross(&up_direction);
-
-        // Step 5: Normalize the Right Direction
-        let normalized_right_direction = right_direction.normalize();
-
-        // Step 6: Compute Final Up Direction
-        normalized_look_direction.cross(&normalized_right_direction)
-    }
-
     pub fn sample_size(&mut self, sample_size: u16) -> &mut Self {
         self.sample_size = Some(sample_size);
         self
@@ -189,18 +636,142 @@ impl CameraBuilder {
         self
     }
 
+    pub fn roll(&mut self, degrees: f64) -> &mut Self {
+        self.roll = Some(degrees);
+        self
+    }
+
     pub fn resolution(&mut self, w: u32, h: u32) -> &mut Self {
         self.resolution = Some((w, h) as Resolution);
         self
     }
 
+    /// Distance from the pinhole to an image one unit high. Replaces the field of view and the
+    /// focal length in millimetres.
     pub fn focal_length(&mut self, focal_length: f64) -> &mut Self {
         self.focal_length = Some(focal_length);
+        self.focal_length_mm = None;
+        self.field_of_view = None;
+        self
+    }
+
+    /// Focal length of a real lens in millimetres, on a sensor `sensor_width` millimetres wide.
+    /// Replaces the field of view and the unitless focal length.
+    pub fn focal_length_mm(&mut self, focal_length_mm: f64) -> &mut Self {
+        self.focal_length_mm = Some(focal_length_mm);
+        self.focal_length = None;
+        self.field_of_view = None;
+        self
+    }
+
+    /// Field of view from the bottom to the top of the image in degrees
+    pub fn vertical_field_of_view(&mut self, degrees: f64) -> &mut Self {
+        self.field_of_view = Some(FieldOfView::Vertical(degrees));
+        self.focal_length = None;
+        self.focal_length_mm = None;
         self
     }
 
+    /// Field of view from the left to the right of the image in degrees
+    pub fn horizontal_field_of_view(&mut self, degrees: f64) -> &mut Self {
+        self.field_of_view = Some(FieldOfView::Horizontal(degrees));
+        self.focal_length = None;
+        self.focal_length_mm = None;
+        self
+    }
+
+    /// Sensor width in millimetres, 36 (35mm film) by default
     pub fn sensor_width(&mut self, sensor_width: f64) -> &mut Self {
         self.sensor_width = Some(sensor_width);
         self
     }
+
+    pub fn exposure(&mut self, exposure: Exposure) -> &mut Self {
+        self.exposure = Some(exposure);
+        self
+    }
+
+    /// The unitless focal length from whichever of the focal lengths or fields of view was set
+    fn image_focal_length(&self, aspect_ratio: f64) -> f64 {
+        let sensor_width = self.sensor_width.unwrap_or(DEFAULT_SENSOR_WIDTH);
+        match (self.field_of_view, self.focal_length_mm, self.focal_length) {
+            (Some(FieldOfView::Vertical(degrees)), _, _) => {
+                0.5 / (degrees.to_radians() / 2.0).tan()
+            }
+            (Some(FieldOfView::Horizontal(degrees)), _, _) => {
+                0.5 * aspect_ratio / (degrees.to_radians() / 2.0).tan()
+            }
+            (None, Some(focal_length_mm), _) => focal_length_mm / sensor_width * aspect_ratio,
+            (None, None, focal_length) => focal_length.unwrap_or(DEFAULT_FOCAL_LENGTH),
+        }
+    }
+
+    pub fn projection(&mut self, projection: Projection) -> &mut Self {
+        self.projection = Some(projection);
+        self
+    }
+
+    pub fn lens_shift(&mut self, lens_shift: f64) -> &mut Self {
+        self.lens_shift = Some(lens_shift);
+        self
+    }
+
+    pub fn eye_offset(&mut self, eye_offset: f64) -> &mut Self {
+        self.eye_offset = Some(eye_offset);
+        self
+    }
+
+    pub fn tile_size(&mut self, tile_size: u32) -> &mut Self {
+        self.tile_size = Some(tile_size);
+        self
+    }
+
+    pub fn tile_order(&mut self, tile_order: TileOrder) -> &mut Self {
+        self.tile_order = Some(tile_order);
+        self
+    }
+
+    /// Keep the shutter open from `open` to `close`, e.g. `(0.0, 1.0 / 60.0)` for a sixtieth of
+    /// a second. Objects and the camera are where their `Motion` puts them at the time of each
+    /// ray.
+    pub fn shutter(&mut self, open: f64, close: f64) -> &mut Self {
+        self.shutter = Some((open, close));
+        self
+    }
+
+    pub fn motion(&mut self, motion: Motion) -> &mut Self {
+        self.motion = Some(motion);
+        self
+    }
+
+    /// Only render the pixels from `x`, `y` at the top left, `width` by `height` pixels big
+    pub fn region(&mut self, x: u32, y: u32, width: u32, height: u32) -> &mut Self {
+        self.region = Some(Tile {
+            x,
+            y,
+            width,
+            height,
+        });
+        self
+    }
+
+    /// Periodically write the render state to `path` so the render can be resumed.
+    pub fn checkpoint(&mut self, path: &str, interval: Duration) -> &mut Self {
+        self.checkpoint_path = Some(path.to_string());
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    /// Seed for the RNG every random choice of the render is made with, from the camera rays to
+    /// the bounces. A random seed is used if none is given.
+    pub fn seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+fn write_checkpoint(render_state: &RenderState, path: &str) {
+    if let Err(err) = render_state.write_to_file(path) {
+        eprintln!("Failed to write checkpoint to {path}: {err}");
+    }
 }
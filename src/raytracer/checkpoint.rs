@@ -0,0 +1,201 @@
+use crate::type_aliases::{Color, Pixels, Resolution};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+const MAGIC: &[u8; 4] = b"RTCP";
+const VERSION: u32 = 1;
+/// Magic, version, width, height and seed
+const HEADER_BYTES: u64 = 24;
+/// The sample count and the three color channels
+const PIXEL_BYTES: u64 = 28;
+
+/// `RenderState` is everything needed to continue a render later on.
+///
+/// `accumulated` holds the sum of all samples for each pixel and `sample_counts` how many samples
+/// went into that sum, so more samples can be added at any time. `seed` is the state of the RNG
+/// used for all sampling. Every pixel gets its own RNG from the seed and its sample count, so a
+/// resumed render continues the same sequence of samples.
+#[derive(Debug, Clone, Default)]
+pub struct RenderState {
+    pub resolution: Resolution,
+    pub seed: u64,
+    pub accumulated: Pixels,
+    pub sample_counts: Vec<u32>,
+}
+
+impl RenderState {
+    pub fn new(resolution: Resolution, seed: u64) -> Self {
+        let total_pixels = resolution.0 as usize * resolution.1 as usize;
+        Self {
+            resolution,
+            seed,
+            accumulated: vec![Color::default(); total_pixels],
+            sample_counts: vec![0; total_pixels],
+        }
+    }
+
+    /// The average color of every pixel. Pixels without samples are black.
+    pub fn average(&self) -> Pixels {
+        self.accumulated
+            .iter()
+            .zip(&self.sample_counts)
+            .map(|(color, &count)| {
+                if count == 0 {
+                    Color::default()
+                } else {
+                    color / count as f64
+                }
+            })
+            .collect()
+    }
+
+    /// Write the state to `path` in a small binary format.
+    ///
+    /// The file is written next to `path` first and then renamed, so a crash while writing
+    /// never destroys the previous checkpoint.
+    pub fn write_to_file(&self, path: &str) -> io::Result<()> {
+        let temporary_path = format!("{path}.tmp");
+        let mut file = BufWriter::new(File::create(&temporary_path)?);
+
+        file.write_all(MAGIC)?;
+        file.write_all(&VERSION.to_le_bytes())?;
+        file.write_all(&self.resolution.0.to_le_bytes())?;
+        file.write_all(&self.resolution.1.to_le_bytes())?;
+        file.write_all(&self.seed.to_le_bytes())?;
+
+        for (color, count) in self.accumulated.iter().zip(&self.sample_counts) {
+            file.write_all(&count.to_le_bytes())?;
+            for channel in color.iter() {
+                file.write_all(&channel.to_le_bytes())?;
+            }
+        }
+
+        file.flush()?;
+        drop(file);
+        std::fs::rename(temporary_path, path)
+    }
+
+    pub fn read_from_file(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let file_length = file.metadata()?.len();
+        let mut file = BufReader::new(file);
+
+        let mut magic = [0; 4];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data(format!("{path} is not a checkpoint file")));
+        }
+
+        let version = read_u32(&mut file)?;
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "unsupported checkpoint version {version}"
+            )));
+        }
+
+        let resolution = (read_u32(&mut file)?, read_u32(&mut file)?);
+        let seed = u64::from_le_bytes(read_bytes(&mut file)?);
+
+        // Check the size in the header against the file before allocating anything for it
+        let expected_length = u64::from(resolution.0)
+            .checked_mul(u64::from(resolution.1))
+            .and_then(|pixels| pixels.checked_mul(PIXEL_BYTES))
+            .and_then(|bytes| bytes.checked_add(HEADER_BYTES));
+        if expected_length != Some(file_length) {
+            let (width, height) = resolution;
+            return Err(invalid_data(format!(
+                "checkpoint size does not match its resolution of {width}x{height}"
+            )));
+        }
+
+        let mut state = Self::new(resolution, seed);
+        for (color, count) in state
+            .accumulated
+            .iter_mut()
+            .zip(state.sample_counts.iter_mut())
+        {
+            *count = read_u32(&mut file)?;
+            for channel in color.iter_mut() {
+                *channel = f64::from_le_bytes(read_bytes(&mut file)?);
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_bytes<const N: usize>(file: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u32(file: &mut impl Read) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_bytes(file)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("rt_{}_{name}", std::process::id()));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut state = RenderState::new((3, 2), 42);
+        state.accumulated[1] = Color::new(0.5, 1.5, 2.5);
+        state.sample_counts[1] = 3;
+        state.sample_counts[5] = 7;
+
+        let path = temporary_path("round_trip.ckpt");
+        state.write_to_file(&path).unwrap();
+        let read = RenderState::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.resolution, state.resolution);
+        assert_eq!(read.seed, state.seed);
+        assert_eq!(read.accumulated, state.accumulated);
+        assert_eq!(read.sample_counts, state.sample_counts);
+    }
+
+    #[test]
+    fn rejects_a_truncated_file() {
+        let path = temporary_path("truncated.ckpt");
+        RenderState::new((4, 4), 0).write_to_file(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+        let error = RenderState::read_from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let path = temporary_path("other.ckpt");
+        std::fs::write(&path, b"P3\n1 1\n255\n0 0 0\n").unwrap();
+
+        let error = RenderState::read_from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn average_leaves_pixels_without_samples_black() {
+        let mut state = RenderState::new((2, 1), 0);
+        state.accumulated[0] = Color::new(2.0, 4.0, 6.0);
+        state.sample_counts[0] = 2;
+
+        assert_eq!(
+            state.average(),
+            vec![Color::new(1.0, 2.0, 3.0), Color::default()]
+        );
+    }
+}
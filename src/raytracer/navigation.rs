@@ -0,0 +1,79 @@
+use crate::type_aliases::{Direction, Directions, Point};
+
+/// Closest the camera gets to looking straight up or down while orbiting, in degrees
+const MAX_ELEVATION: f64 = 89.0;
+
+/// `Viewpoint` is where a camera is and the point it looks at, with the moves of an interactive
+/// viewport. Every move returns a new viewpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewpoint {
+    pub position: Point,
+    pub look_at: Point,
+}
+
+impl Viewpoint {
+    pub fn new(position: Point, look_at: Point) -> Self {
+        Self { position, look_at }
+    }
+
+    pub fn distance(&self) -> f64 {
+        (self.position - self.look_at).norm()
+    }
+
+    /// Forward, right and up directions, with up as close to the Y-axis as possible
+    pub fn axes(&self) -> (Direction, Direction, Direction) {
+        let forward = (self.look_at - self.position)
+            .try_normalize(1e-12)
+            .unwrap_or(Direction::new(0.0, 0.0, -1.0));
+        let right = forward
+            .cross(&Direction::up())
+            .try_normalize(1e-9)
+            .unwrap_or(Direction::new(1.0, 0.0, 0.0));
+        (forward, right, right.cross(&forward))
+    }
+
+    /// Circle around the look at point. Positive `yaw` moves the camera to its right around the
+    /// Y-axis and positive `pitch` moves it up, both in degrees.
+    pub fn orbit(&self, yaw: f64, pitch: f64) -> Self {
+        let offset = self.position - self.look_at;
+        let distance = offset.norm();
+        if distance < 1e-12 {
+            return *self;
+        }
+
+        let azimuth = offset.z.atan2(offset.x) - yaw.to_radians();
+        let elevation = ((offset.y / distance).clamp(-1.0, 1.0).asin() + pitch.to_radians())
+            .clamp(-MAX_ELEVATION.to_radians(), MAX_ELEVATION.to_radians());
+
+        let offset = Point::new(
+            elevation.cos() * azimuth.cos(),
+            elevation.sin(),
+            elevation.cos() * azimuth.sin(),
+        ) * distance;
+        Self::new(self.look_at + offset, self.look_at)
+    }
+
+    /// Slide the camera and the look at point sideways and up. The amounts are fractions of the
+    /// distance between them, so panning feels the same however far away the camera is.
+    pub fn pan(&self, right: f64, up: f64) -> Self {
+        let (_, right_direction, up_direction) = self.axes();
+        let offset = (right_direction * right + up_direction * up) * self.distance();
+        Self::new(self.position + offset, self.look_at + offset)
+    }
+
+    /// Move towards the look at point, multiplying the distance by `factor`
+    pub fn zoom(&self, factor: f64) -> Self {
+        let offset = (self.position - self.look_at) * factor;
+        if offset.norm() < 1e-3 {
+            return *self;
+        }
+        Self::new(self.look_at + offset, self.look_at)
+    }
+
+    /// Move forward, right and up in scene units, taking the look at point along
+    pub fn fly(&self, forward: f64, right: f64, up: f64) -> Self {
+        let (forward_direction, right_direction, _) = self.axes();
+        let offset = forward_direction * forward + right_direction * right + Direction::up() * up;
+        Self::new(self.position + offset, self.look_at + offset)
+    }
+}
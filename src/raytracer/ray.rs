@@ -1,3 +1,5 @@
+use crate::color::RGB;
+use crate::textures::Medium;
 use crate::type_aliases::Directions;
 use crate::{config::rays::*, textures::Texture, type_aliases::Color};
 
@@ -6,9 +8,17 @@ pub struct Ray {
     pub origin: Point,
     pub direction: Direction,
     pub collisions: Vec<Color>,
+    /// Light from the scene's light sources arriving at each collision, sampled with shadow rays
+    pub direct_light: Vec<Color>,
     pub hit_light_source: bool,
     pub intersection_dist: f64,
     pub depth: u8,
+    /// The moment within the camera's shutter interval the ray was sent at. Moving objects are
+    /// intersected where they are at this time.
+    pub time: f64,
+    /// A random number between 0.0 and 1.0 for objects that are hit at a random distance, like
+    /// `Volume`. It comes from the pixel's RNG, so a resumed render repeats the same samples.
+    pub sample: f64,
 }
 
 impl Ray {
@@ -17,52 +27,167 @@ impl Ray {
             origin,
             direction: direction.normalize(),
             collisions: Vec::new(),
+            direct_light: Vec::new(),
             hit_light_source: false,
             intersection_dist: f64::MAX,
             depth,
+            time: 0.0,
+            sample: 0.5,
         }
     }
 
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn with_sample(mut self, rng: &mut impl Rng) -> Self {
+        self.sample = rng.gen();
+        self
+    }
+
     /// Find the closest intersection, create a new ray based on the surface, and recursively call
-    /// `trace`
-    pub fn trace(&mut self, scene: &Scene) {
+    /// `trace`. All random choices are made with `rng`.
+    pub fn trace(&mut self, scene: &Scene, rng: &mut impl Rng) {
         if self.reached_max_depth() {
             return;
         }
 
+        let closest_intersection = self.closest_intersection(scene);
+
+        // Scatter in the fog if the ray interacts with it before reaching the closest object
+        if let Some(fog) = &scene.fog {
+            let distance = fog.sample_distance(rng.gen());
+            let max_distance = closest_intersection
+                .as_ref()
+                .map_or(f64::INFINITY, |intersection| intersection.distance);
+
+            if distance < max_distance {
+                self.scatter(fog, self.origin + self.direction * distance, scene, rng);
+                return;
+            }
+        }
+
         // Process the closest intersection
-        if let Some(intersection) = self.closest_intersection(scene) {
+        if let Some(intersection) = closest_intersection {
             let small_offset = 1e-3 * intersection.normal;
             let origin = intersection.hit_point + small_offset;
             let normal = intersection.normal;
 
             // Reflect based on object texture
             match intersection.texture {
-                Texture::Diffusive(color) => {
-                    self.collisions.push(color);
-                    let direction = self.diffuse_direction(normal);
-                    if direction.near_zero() {
-                        self.reflect(origin, normal, scene);
-                    } else {
-                        self.reflect(origin, direction, scene);
-                    }
+                Texture::Diffusive(color) => self.diffuse(color, origin, normal, scene, rng),
+                Texture::Procedural(procedural) => {
+                    let color = procedural.color_at(intersection.local_point);
+                    self.diffuse(color, origin, normal, scene, rng);
+                }
+                Texture::Image(image) => {
+                    let color = image.color_at(intersection.uv);
+                    self.diffuse(color, origin, normal, scene, rng);
                 }
                 Texture::Reflective => {
                     let direction = self.perfect_reflection(normal);
-                    self.reflect(origin, direction, scene);
+                    self.reflect(origin, direction, scene, rng);
                 }
 
-                Texture::Light(color) => {
-                    self.collisions.push(color);
+                Texture::Light(emission) => {
+                    self.collisions.push(emission.radiance());
+                    self.direct_light.push(Color::black());
                     self.hit_light_source = true;
                 }
+
+                Texture::Volume(medium) => {
+                    self.scatter(&medium, intersection.hit_point, scene, rng);
+                }
+            }
+        }
+    }
+
+    /// Bounce off a diffusive surface with `color` in a random direction
+    fn diffuse(
+        &mut self,
+        color: Color,
+        origin: Point,
+        normal: Normal,
+        scene: &Scene,
+        rng: &mut impl Rng,
+    ) {
+        self.collisions.push(color);
+        self.direct_light
+            .push(self.sample_lights(origin, scene, rng, |direction| normal.dot(&direction)));
+        let direction = self.diffuse_direction(normal, rng);
+        if direction.near_zero() {
+            self.reflect(origin, normal, scene, rng);
+        } else {
+            self.reflect(origin, direction, scene, rng);
+        }
+    }
+
+    /// Scatter the ray at `point` inside `medium` and continue in a direction given by the
+    /// phase function. The light that is not absorbed keeps travelling.
+    fn scatter(&mut self, medium: &Medium, point: Point, scene: &Scene, rng: &mut impl Rng) {
+        self.collisions.push(medium.albedo());
+
+        // Weighted relative to a medium scattering equally in all directions
+        let direction = self.direction;
+        self.direct_light
+            .push(self.sample_lights(point, scene, rng, |to_light| {
+                4.0 * std::f64::consts::PI * medium.phase(direction.dot(&to_light))
+            }));
+
+        let direction = medium.scatter_direction(self.direction, rng);
+        self.reflect(point, direction, scene, rng);
+    }
+
+    /// Sum of the light from all light sources reaching `point`, multiplied by `weight` of the
+    /// direction towards the light. Lights blocked by an object are skipped, and fog dims the
+    /// light on its way.
+    fn sample_lights<W>(&self, point: Point, scene: &Scene, rng: &mut impl Rng, weight: W) -> Color
+    where
+        W: Fn(Direction) -> f64,
+    {
+        let mut total = Color::black();
+        for light in &scene.lights {
+            let Some(sample) = light.illuminate(point) else {
+                continue;
+            };
+
+            let weight = weight(sample.direction);
+            if weight <= 0.0 {
+                continue;
             }
+
+            let mut shadow_ray = Ray::new(point, sample.direction, self.depth)
+                .with_time(self.time)
+                .with_sample(rng);
+            shadow_ray.intersection_dist = sample.distance;
+            if shadow_ray.is_blocked(scene, sample.distance) {
+                continue;
+            }
+
+            // Directional lights are infinitely far away, so only dim lights at a distance
+            let transmittance = match &scene.fog {
+                Some(fog) if sample.distance.is_finite() => fog.transmittance(sample.distance),
+                _ => 1.0,
+            };
+
+            total += sample.radiance * weight * transmittance;
         }
+        total
+    }
+
+    /// Check if any object is in the way before `distance`
+    fn is_blocked(&self, scene: &Scene, distance: f64) -> bool {
+        scene.all_objects().any(|object| {
+            object
+                .intersection(self)
+                .is_some_and(|intersection| intersection.distance < distance)
+        })
     }
 
     fn closest_intersection(&mut self, scene: &Scene) -> Option<Intersection> {
         let mut closest_intersection: Option<Intersection> = None;
-        for object in &scene.objects {
+        for object in scene.all_objects() {
             if let Some(intersection) = object.intersection(self) {
                 if intersection.distance < self.intersection_dist {
                     self.intersection_dist = intersection.distance;
@@ -76,9 +201,7 @@ impl Ray {
     /// ### diffuse_direction
     ///
     /// Generate a random direction for diffuse reflection on a hemisphere given a surface normal
-    fn diffuse_direction(&self, normal: Normal) -> Direction {
-        let mut rng = rand::thread_rng();
-
+    fn diffuse_direction(&self, normal: Normal, rng: &mut impl Rng) -> Direction {
         // Create a local coordinate system around the normal
         let incident_ray = normal.normalize();
         let tangent_a = if incident_ray.x.abs() > 0.9 {
@@ -105,12 +228,21 @@ impl Ray {
     fn perfect_reflection(&self, normal: Normal) -> Direction {
         self.direction - 2.0 * self.direction.dot(&normal) * normal
     }
-    pub fn reflect(&mut self, origin: Point, direction: Direction, scene: &Scene) {
-        let mut secondary_ray = Ray::new(origin, direction, self.depth + 1);
-
-        secondary_ray.trace(scene);
+    pub fn reflect(
+        &mut self,
+        origin: Point,
+        direction: Direction,
+        scene: &Scene,
+        rng: &mut impl Rng,
+    ) {
+        let mut secondary_ray = Ray::new(origin, direction, self.depth + 1)
+            .with_time(self.time)
+            .with_sample(rng);
+
+        secondary_ray.trace(scene, rng);
 
         self.collisions.extend(secondary_ray.collisions);
+        self.direct_light.extend(secondary_ray.direct_light);
 
         if secondary_ray.hit_light_source {
             self.hit_light_source = true;
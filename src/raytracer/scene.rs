@@ -1,12 +1,21 @@
 use std::sync::Arc;
 
 use crate::color::RGB;
+use crate::lights::Lights;
 use crate::objects::*;
+use crate::raytracer::Ray;
+use crate::textures::Medium;
 use crate::textures::Texture::*;
 use crate::type_aliases::{Color, Point};
 
+#[derive(Clone)]
 pub struct Scene {
     pub objects: Objects,
+    /// Named groups of objects, traced together with `objects`
+    pub groups: Vec<Group>,
+    pub lights: Lights,
+    /// Fog filling the whole scene
+    pub fog: Option<Medium>,
     pub brightness: f64,
 }
 
@@ -33,6 +42,9 @@ impl Scene {
 
         Self {
             objects,
+            groups: Vec::new(),
+            lights: Vec::new(),
+            fog: None,
             brightness: if brightness <= 0.0 {
                 0.0001
             } else {
@@ -41,6 +53,49 @@ impl Scene {
         }
     }
 
+    /// Every object and group in the scene
+    pub fn all_objects(&self) -> impl Iterator<Item = &dyn Object> {
+        self.objects
+            .iter()
+            .map(|object| object.as_ref())
+            .chain(self.groups.iter().map(|group| group as &dyn Object))
+    }
+
+    /// The closest object hit by `ray`. Objects in groups are returned as they were added to the
+    /// group, so they can be told apart even though the scene traces them transformed.
+    pub fn hit_object(&self, ray: &Ray) -> Option<Arc<dyn Object>> {
+        let mut ray = ray.clone();
+        let mut closest_object = None;
+        for object in &self.objects {
+            if let Some(intersection) = object.intersection(&ray) {
+                if intersection.distance < ray.intersection_dist {
+                    ray.intersection_dist = intersection.distance;
+                    closest_object = Some(object.clone());
+                }
+            }
+        }
+        for group in &self.groups {
+            if let Some((object, intersection)) = group.hit_object(&ray) {
+                if intersection.distance < ray.intersection_dist {
+                    ray.intersection_dist = intersection.distance;
+                    closest_object = Some(object);
+                }
+            }
+        }
+        closest_object
+    }
+
+    /// The first group called `name`, also searching inside other groups
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find_map(|group| group.find(name))
+    }
+
+    pub fn group_mut(&mut self, name: &str) -> Option<&mut Group> {
+        self.groups
+            .iter_mut()
+            .find_map(|group| group.find_mut(name))
+    }
+
     pub fn background(&self) -> Color {
         Color::white() * self.brightness
     }
@@ -0,0 +1,149 @@
+use crate::raytracer::{Camera, CameraBuilder, Projection, Scene};
+use crate::textures::Image;
+use std::io;
+use std::sync::Arc;
+
+/// How the two eyes of a stereo camera are aimed at the convergence distance
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Convergence {
+    /// Both eyes turn towards the same point. Simple, but distorts the edges of the image.
+    ToeIn,
+    /// The eyes look straight ahead and the images are shifted instead, like most stereo rigs.
+    /// Only a `Perspective` projection can be shifted, the others turn the eyes as with `ToeIn`.
+    #[default]
+    OffAxis,
+}
+
+/// How the images of both eyes are saved
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StereoLayout {
+    /// One image twice as wide, the left eye on the left
+    #[default]
+    SideBySide,
+    /// One image twice as high, the left eye on top
+    OverUnder,
+    /// Two images, with `_left` and `_right` added to the file name
+    Separate,
+}
+
+/// `Stereo` renders a scene once for each eye, for VR headsets and 3D displays.
+///
+/// The eyes are `interocular_distance` apart, in scene units, and things at
+/// `convergence_distance` appear at the depth of the screen. With an `Equirectangular`
+/// projection the result is an omni-directional stereo panorama.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stereo {
+    pub interocular_distance: f64,
+    /// Distance from the camera to the plane where both eyes see the same image. By default the
+    /// distance to the `look_at` point.
+    pub convergence_distance: Option<f64>,
+    pub convergence: Convergence,
+    pub layout: StereoLayout,
+}
+
+impl Stereo {
+    pub fn new(interocular_distance: f64) -> Self {
+        Self {
+            interocular_distance,
+            convergence_distance: None,
+            convergence: Convergence::default(),
+            layout: StereoLayout::default(),
+        }
+    }
+
+    pub fn with_convergence_distance(mut self, convergence_distance: f64) -> Self {
+        self.convergence_distance = Some(convergence_distance);
+        self
+    }
+
+    pub fn with_convergence(mut self, convergence: Convergence) -> Self {
+        self.convergence = convergence;
+        self
+    }
+
+    pub fn with_layout(mut self, layout: StereoLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Cameras for the left and the right eye, set up like `builder` otherwise
+    pub fn eye_cameras(&self, builder: &CameraBuilder) -> (Camera, Camera) {
+        let center = builder.build();
+        let (right, _, view_direction) = center.basis();
+        let convergence_distance = self
+            .convergence_distance
+            .unwrap_or_else(|| (center.look_at - center.position).norm());
+
+        let eye = |side: f64| {
+            let offset = side * self.interocular_distance / 2.0;
+            let mut eye_builder = builder.clone();
+
+            if center.projection == Projection::Equirectangular {
+                return eye_builder.eye_offset(offset).build();
+            }
+
+            let position = center.position + right * offset;
+            eye_builder.position_by_coordinates(position);
+            // `lens_shift` only moves perspective images
+            let convergence = match center.projection {
+                Projection::Perspective => self.convergence,
+                _ => Convergence::ToeIn,
+            };
+            match convergence {
+                Convergence::ToeIn => {
+                    eye_builder.look_at(center.position - view_direction * convergence_distance);
+                }
+                Convergence::OffAxis => {
+                    let shift = -offset * center.focal_length / convergence_distance;
+                    eye_builder
+                        .look_at(center.look_at + right * offset)
+                        .lens_shift(center.lens_shift + shift);
+                }
+            }
+            eye_builder.build()
+        };
+
+        (eye(-1.0), eye(1.0))
+    }
+
+    /// Render both eyes and save them to `path` in `layout`
+    pub fn render(&self, builder: &CameraBuilder, scene: Arc<Scene>, path: &str) -> io::Result<()> {
+        let (mut left, mut right) = self.eye_cameras(builder);
+        left.send_rays(scene.clone());
+        right.send_rays(scene);
+        self.write(&left.image(), &right.image(), path)
+    }
+
+    pub fn write(&self, left: &Image, right: &Image, path: &str) -> io::Result<()> {
+        match self.layout {
+            StereoLayout::SideBySide => {
+                let pixels = left
+                    .pixels
+                    .chunks(left.width as usize)
+                    .zip(right.pixels.chunks(right.width as usize))
+                    .flat_map(|(left_row, right_row)| left_row.iter().chain(right_row))
+                    .copied()
+                    .collect();
+                let image = Image {
+                    width: left.width * 2,
+                    height: left.height,
+                    pixels,
+                };
+                image.save(path)
+            }
+            StereoLayout::OverUnder => {
+                let image = Image {
+                    width: left.width,
+                    height: left.height * 2,
+                    pixels: [left.pixels.as_slice(), &right.pixels].concat(),
+                };
+                image.save(path)
+            }
+            StereoLayout::Separate => {
+                let (stem, extension) = path.rsplit_once('.').unwrap_or((path, "png"));
+                left.save(&format!("{stem}_left.{extension}"))?;
+                right.save(&format!("{stem}_right.{extension}"))
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::type_aliases::Resolution;
+
+/// The order in which tiles are handed out to the rayon workers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileOrder {
+    /// Start at the center of the image and spiral outwards.
+    #[default]
+    Spiral,
+    /// Follow a [Hilbert curve](https://en.wikipedia.org/wiki/Hilbert_curve) over the tile grid.
+    Hilbert,
+}
+
+/// `Tile` is a rectangular block of pixels in the image.
+///
+/// `x` and `y` are the pixel coordinates of the top left corner. Tiles on the right and bottom
+/// edges of the image are cropped, so `width` and `height` can be smaller than the tile size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Tile {
+    pub fn pixel_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// The part of this tile inside `other`, if they overlap. Tiles reaching past `u32::MAX`,
+    /// like a region given on the command line, end there.
+    pub fn clip(&self, other: &Tile) -> Option<Tile> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self
+            .x
+            .saturating_add(self.width)
+            .min(other.x.saturating_add(other.width));
+        let bottom = self
+            .y
+            .saturating_add(self.height)
+            .min(other.y.saturating_add(other.height));
+        (x < right && y < bottom).then(|| Tile {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        })
+    }
+
+    /// Iterate over the `(column, row)` image coordinates of the tile, row by row.
+    pub fn coordinates(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        (self.y..self.y + self.height)
+            .flat_map(move |row| (self.x..self.x + self.width).map(move |column| (column, row)))
+    }
+}
+
+/// Split the image into square tiles of `tile_size` pixels, sorted by `order`.
+pub fn create_tiles(resolution: Resolution, tile_size: u32, order: TileOrder) -> Vec<Tile> {
+    let (width, height) = resolution;
+    let tile_size = tile_size.max(1);
+    let columns = width.div_ceil(tile_size);
+    let rows = height.div_ceil(tile_size);
+
+    let mut grid: Vec<(u32, u32)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .collect();
+
+    match order {
+        TileOrder::Spiral => {
+            let center_x = (columns as f64 - 1.0) / 2.0;
+            let center_y = (rows as f64 - 1.0) / 2.0;
+            grid.sort_by(|a, b| {
+                spiral_key(*a, center_x, center_y)
+                    .partial_cmp(&spiral_key(*b, center_x, center_y))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        TileOrder::Hilbert => {
+            let side = columns.max(rows).next_power_of_two();
+            grid.sort_by_key(|&(column, row)| hilbert_index(side, column, row));
+        }
+    }
+
+    grid.into_iter()
+        .map(|(column, row)| {
+            let x = column * tile_size;
+            let y = row * tile_size;
+            Tile {
+                x,
+                y,
+                width: tile_size.min(width - x),
+                height: tile_size.min(height - y),
+            }
+        })
+        .collect()
+}
+
+/// Sort key for the spiral order: first by the square ring around the center, then by the angle
+/// within that ring.
+fn spiral_key((column, row): (u32, u32), center_x: f64, center_y: f64) -> (f64, f64) {
+    let dx = column as f64 - center_x;
+    let dy = row as f64 - center_y;
+    let ring = dx.abs().max(dy.abs()).floor();
+    (ring, dy.atan2(dx))
+}
+
+/// Distance along a Hilbert curve covering a `side` x `side` grid, where `side` is a power of two.
+fn hilbert_index(side: u32, column: u32, row: u32) -> u64 {
+    let (mut x, mut y) = (column as u64, row as u64);
+    let mut index = 0;
+    let mut s = side as u64 / 2;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        index += s * s * ((3 * rx) ^ ry);
+
+        // Rotate the quadrant so the curve stays continuous
+        if ry == 0 {
+            if rx == 1 {
+                x = side as u64 - 1 - x;
+                y = side as u64 - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(x: u32, y: u32, width: u32, height: u32) -> Tile {
+        Tile {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn clip_keeps_the_overlap() {
+        let clipped = tile(0, 0, 16, 16).clip(&tile(10, 4, 20, 8));
+        assert_eq!(clipped, Some(tile(10, 4, 6, 8)));
+    }
+
+    #[test]
+    fn clip_without_overlap_is_none() {
+        assert_eq!(tile(0, 0, 16, 16).clip(&tile(16, 0, 16, 16)), None);
+        assert_eq!(tile(0, 0, 16, 16).clip(&tile(0, 20, 4, 4)), None);
+    }
+
+    #[test]
+    fn clip_ends_regions_at_u32_max() {
+        let region = tile(u32::MAX - 4, 2, u32::MAX, u32::MAX);
+        let clipped = tile(u32::MAX - 8, 0, 8, 8).clip(&region);
+        assert_eq!(clipped, Some(tile(u32::MAX - 4, 2, 4, 6)));
+    }
+
+    #[test]
+    fn tiles_cover_every_pixel_once() {
+        for order in [TileOrder::Spiral, TileOrder::Hilbert] {
+            let tiles = create_tiles((37, 23), 8, order);
+            let mut covered = vec![0; 37 * 23];
+            for tile in &tiles {
+                for (column, row) in tile.coordinates() {
+                    covered[(row * 37 + column) as usize] += 1;
+                }
+            }
+            assert!(covered.iter().all(|&count| count == 1), "{order:?}");
+        }
+    }
+
+    #[test]
+    fn spiral_starts_in_the_center() {
+        let tiles = create_tiles((48, 48), 16, TileOrder::Spiral);
+        assert_eq!(tiles[0], tile(16, 16, 16, 16));
+        let rings: Vec<f64> = [(1, 1), (0, 1), (2, 2)]
+            .into_iter()
+            .map(|cell| spiral_key(cell, 1.0, 1.0).0)
+            .collect();
+        assert_eq!(rings, [0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn hilbert_steps_to_a_neighbour_every_time() {
+        let side = 8;
+        let mut cells: Vec<(u32, u32)> = (0..side)
+            .flat_map(|row| (0..side).map(move |column| (column, row)))
+            .collect();
+        cells.sort_by_key(|&(column, row)| hilbert_index(side, column, row));
+
+        assert_eq!(cells[0], (0, 0));
+        for pair in cells.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            assert_eq!(x0.abs_diff(x1) + y0.abs_diff(y1), 1, "{pair:?}");
+        }
+    }
+}
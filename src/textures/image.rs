@@ -0,0 +1,249 @@
+use crate::type_aliases::{Color, Uv};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// How the color between the pixels of an image is looked up
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Filter {
+    /// Use the closest pixel
+    Nearest,
+    /// Blend the four closest pixels
+    #[default]
+    Bilinear,
+}
+
+/// What happens to UV coordinates outside of 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Wrap {
+    /// Tile the image
+    #[default]
+    Repeat,
+    /// Tile the image, flipping every other copy
+    Mirror,
+    /// Stretch the pixels on the edge
+    Clamp,
+}
+
+/// `Image` is a decoded image with colors in the 0-255 range, stored row by row from the top.
+#[derive(Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Color>,
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Image({}x{})", self.width, self.height)
+    }
+}
+
+impl Image {
+    /// Load a PPM (`.ppm`) image, or a PNG or any other format supported by gdk-pixbuf
+    pub fn load(path: &str) -> io::Result<Self> {
+        let is_ppm = Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ppm"));
+
+        if is_ppm {
+            Self::from_ppm(&std::fs::read(path)?)
+        } else {
+            super::pixbuf::read_image(path)
+        }
+    }
+
+    /// Save as PNG, JPEG or any other format gdk-pixbuf can write, picked by the extension of
+    /// `path`
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        super::pixbuf::write_image(path, self)
+    }
+
+    /// Decode a plain (P3) or binary (P6) PPM image
+    pub fn from_ppm(data: &[u8]) -> io::Result<Self> {
+        let mut position = 0;
+        let mut next_token = || -> io::Result<String> {
+            // Skip whitespace and comments
+            loop {
+                match data.get(position) {
+                    Some(b'#') => {
+                        while data.get(position).is_some_and(|&byte| byte != b'\n') {
+                            position += 1;
+                        }
+                    }
+                    Some(byte) if byte.is_ascii_whitespace() => position += 1,
+                    Some(_) => break,
+                    None => return Err(invalid_ppm("unexpected end of file")),
+                }
+            }
+
+            let start = position;
+            while data
+                .get(position)
+                .is_some_and(|byte| !byte.is_ascii_whitespace())
+            {
+                position += 1;
+            }
+            Ok(String::from_utf8_lossy(&data[start..position]).into_owned())
+        };
+
+        let magic = next_token()?;
+        let mut number = || -> io::Result<u32> {
+            next_token()?
+                .parse()
+                .map_err(|_| invalid_ppm("invalid number"))
+        };
+        let width = number()?;
+        let height = number()?;
+        let max_value = number()?.max(1) as f64;
+        // Every pixel takes at least a byte, so a larger image than the file is invalid
+        let total_pixels = (width as usize)
+            .checked_mul(height as usize)
+            .filter(|&total_pixels| total_pixels <= data.len())
+            .ok_or_else(|| invalid_ppm("image too large"))?;
+
+        let pixels = match magic.as_str() {
+            "P3" => (0..total_pixels)
+                .map(|_| -> io::Result<Color> {
+                    let [r, g, b] = [number()?, number()?, number()?].map(|v| v as f64);
+                    Ok(Color::new(r, g, b) * (255.0 / max_value))
+                })
+                .collect::<io::Result<Vec<Color>>>()?,
+            "P6" => {
+                // A single whitespace separates the header from the pixel data
+                let bytes_per_channel = if max_value > 255.0 { 2 } else { 1 };
+                let start = position + 1;
+                let end = start + total_pixels * 3 * bytes_per_channel;
+                let bytes = data
+                    .get(start..end)
+                    .ok_or_else(|| invalid_ppm("not enough pixel data"))?;
+
+                bytes
+                    .chunks(3 * bytes_per_channel)
+                    .map(|pixel| {
+                        let channel = |i: usize| {
+                            if bytes_per_channel == 2 {
+                                u16::from_be_bytes([pixel[2 * i], pixel[2 * i + 1]]) as f64
+                            } else {
+                                pixel[i] as f64
+                            }
+                        };
+                        Color::new(channel(0), channel(1), channel(2)) * (255.0 / max_value)
+                    })
+                    .collect()
+            }
+            _ => return Err(invalid_ppm("only P3 and P6 images are supported")),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn pixel(&self, x: i64, y: i64) -> Color {
+        let x = x.clamp(0, self.width as i64 - 1);
+        let y = y.clamp(0, self.height as i64 - 1);
+        self.pixels[(y * self.width as i64 + x) as usize]
+    }
+}
+
+fn invalid_ppm(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid PPM: {message}"),
+    )
+}
+
+/// `ImageTexture` wraps an image around an object using the UV coordinates of the hit point.
+///
+/// `scale` repeats the image that many times across the surface. The image is shared, so many
+/// objects can use the same texture without copying it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageTexture {
+    pub image: Arc<Image>,
+    pub filter: Filter,
+    pub wrap: Wrap,
+    pub scale: f64,
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<Image>, filter: Filter, wrap: Wrap) -> Self {
+        Self {
+            image,
+            filter,
+            wrap,
+            scale: 1.0,
+        }
+    }
+
+    pub fn load(path: &str, filter: Filter, wrap: Wrap) -> io::Result<Self> {
+        Ok(Self::new(Arc::new(Image::load(path)?), filter, wrap))
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The color at `uv`. (0.0, 0.0) is the bottom left corner of the image.
+    pub fn color_at(&self, uv: Uv) -> Color {
+        let image = &self.image;
+        if image.pixels.is_empty() {
+            return Color::default();
+        }
+
+        let u = self.wrap(uv.x * self.scale);
+        let v = self.wrap(uv.y * self.scale);
+
+        // Pixel coordinates, with the centers of the pixels at whole numbers
+        let x = u * image.width as f64 - 0.5;
+        let y = (1.0 - v) * image.height as f64 - 0.5;
+
+        match self.filter {
+            Filter::Nearest => image.pixel(x.round() as i64, y.round() as i64),
+            Filter::Bilinear => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let [x1, y1] = [
+                    self.wrap_pixel(x0 + 1, image.width),
+                    self.wrap_pixel(y0 + 1, image.height),
+                ];
+                let [x0, y0] = [
+                    self.wrap_pixel(x0, image.width),
+                    self.wrap_pixel(y0, image.height),
+                ];
+
+                let top = image.pixel(x0, y0) * (1.0 - tx) + image.pixel(x1, y0) * tx;
+                let bottom = image.pixel(x0, y1) * (1.0 - tx) + image.pixel(x1, y1) * tx;
+                top * (1.0 - ty) + bottom * ty
+            }
+        }
+    }
+
+    fn wrap(&self, t: f64) -> f64 {
+        match self.wrap {
+            Wrap::Repeat => t.rem_euclid(1.0),
+            Wrap::Mirror => {
+                let t = t.rem_euclid(2.0);
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+            Wrap::Clamp => t.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Neighbouring pixels for the bilinear filter wrap around the edges when repeating
+    fn wrap_pixel(&self, index: i64, size: u32) -> i64 {
+        match self.wrap {
+            Wrap::Repeat => index.rem_euclid(size as i64),
+            Wrap::Mirror | Wrap::Clamp => index,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::objects::Distance;
+use crate::type_aliases::{Color, Direction};
+use nalgebra::Vector3;
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// `Medium` is a homogeneous participating medium like fog or smoke.
+///
+/// `absorption` and `scattering` are the coefficients per unit of distance. `color` tints the
+/// scattered light and `anisotropy` is the `g` parameter of the
+/// [Henyey-Greenstein](https://www.astro.umd.edu/~jph/HG_note.pdf) phase function: 0.0 scatters
+/// equally in all directions, positive values scatter forward and negative values backward.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Medium {
+    pub absorption: f64,
+    pub scattering: f64,
+    pub color: Color,
+    pub anisotropy: f64,
+}
+
+impl Medium {
+    pub fn new(absorption: f64, scattering: f64, color: Color, anisotropy: f64) -> Self {
+        Self {
+            absorption: absorption.max(0.0),
+            scattering: scattering.max(0.0),
+            color,
+            anisotropy: anisotropy.clamp(-0.99, 0.99),
+        }
+    }
+
+    pub fn extinction(&self) -> f64 {
+        self.absorption + self.scattering
+    }
+
+    /// The color of the light that is scattered instead of absorbed
+    pub fn albedo(&self) -> Color {
+        if self.extinction() <= 0.0 {
+            return Color::default();
+        }
+        self.color * (self.scattering / self.extinction())
+    }
+
+    /// Random distance a ray travels in the medium before it interacts with it, for a random
+    /// number `sample` between 0.0 and 1.0
+    pub fn sample_distance(&self, sample: f64) -> Distance {
+        if self.extinction() <= 0.0 {
+            return f64::INFINITY;
+        }
+        -(1.0 - sample).ln() / self.extinction()
+    }
+
+    /// How much light makes it through `distance` of the medium, from 0.0 to 1.0
+    pub fn transmittance(&self, distance: Distance) -> f64 {
+        (-self.extinction() * distance).exp()
+    }
+
+    /// The phase function for light turning by an angle with cosine `cos_theta`
+    pub fn phase(&self, cos_theta: f64) -> f64 {
+        let g = self.anisotropy;
+        let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+    }
+
+    /// Sample a new direction for a ray travelling in `direction` that scatters in the medium
+    pub fn scatter_direction(&self, direction: Direction, rng: &mut impl Rng) -> Direction {
+        let g = self.anisotropy;
+        let rand_1: f64 = rng.gen();
+        let rand_2: f64 = rng.gen();
+
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * rand_1
+        } else {
+            let square = (1.0 - g * g) / (1.0 - g + 2.0 * g * rand_1);
+            (1.0 + g * g - square * square) / (2.0 * g)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand_2;
+
+        // Create a local coordinate system around the direction
+        let forward = direction.normalize();
+        let tangent_a = if forward.x.abs() > 0.9 {
+            Vector3::new(0.0, 1.0, 0.0)
+        } else {
+            Vector3::new(1.0, 0.0, 0.0)
+        };
+        let tangent_v = forward.cross(&tangent_a).normalize();
+        let tangent_u = forward.cross(&tangent_v);
+
+        tangent_u * (phi.cos() * sin_theta)
+            + tangent_v * (phi.sin() * sin_theta)
+            + forward * cos_theta
+    }
+}
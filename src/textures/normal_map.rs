@@ -0,0 +1,95 @@
+use crate::objects::Intersection;
+use crate::textures::{ImageTexture, Procedural};
+use crate::type_aliases::{Color, Normal, Point, Uv};
+
+/// Where the height of a bump map comes from. Brighter is higher.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeightSource {
+    /// Looked up with the UV coordinates of the hit point
+    Image(ImageTexture),
+    /// Evaluated at the hit point in the coordinates of the object
+    Procedural(Procedural),
+}
+
+/// `NormalMap` changes the shading normal of a surface to fake small details like scratches,
+/// bricks or ripples, without changing the geometry.
+///
+/// `strength` scales the effect, 0.0 leaves the surface smooth.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NormalMap {
+    /// A height map, the normal is tilted along the slopes of the height.
+    Bump { height: HeightSource, strength: f64 },
+    /// A tangent-space normal map as exported by most texturing tools. Red, green and blue are
+    /// the normal along the tangent, the bitangent and the geometric normal.
+    TangentSpace {
+        normal_map: ImageTexture,
+        strength: f64,
+    },
+}
+
+impl NormalMap {
+    /// The shading normal at the intersection
+    pub fn perturb(&self, intersection: &Intersection) -> Normal {
+        let normal = intersection.normal;
+        let (tangent, bitangent) = (intersection.tangent, intersection.bitangent);
+
+        let perturbed = match self {
+            NormalMap::Bump {
+                height: HeightSource::Image(image),
+                strength,
+            } => {
+                // Slopes over one pixel of the height map
+                let du = 1.0 / (image.image.width.max(1) as f64 * image.scale);
+                let dv = 1.0 / (image.image.height.max(1) as f64 * image.scale);
+                let uv = intersection.uv;
+                let height = luminance(image.color_at(uv));
+                let slope_u = luminance(image.color_at(uv + Uv::new(du, 0.0))) - height;
+                let slope_v = luminance(image.color_at(uv + Uv::new(0.0, dv))) - height;
+
+                normal - (tangent * slope_u + bitangent * slope_v) * *strength
+            }
+            NormalMap::Bump {
+                height: HeightSource::Procedural(procedural),
+                strength,
+            } => {
+                let gradient = height_gradient(procedural, intersection.local_point);
+                let surface_gradient = gradient - normal * gradient.dot(&normal);
+
+                normal - surface_gradient * *strength
+            }
+            NormalMap::TangentSpace {
+                normal_map,
+                strength,
+            } => {
+                let color = normal_map.color_at(intersection.uv) / 255.0 * 2.0;
+                let mapped = tangent * (color.x - 1.0)
+                    + bitangent * (color.y - 1.0)
+                    + normal * (color.z - 1.0);
+
+                normal + (mapped.normalize() - normal) * *strength
+            }
+        };
+
+        // Never tilt the normal to the other side of the surface
+        match perturbed.try_normalize(1e-9) {
+            Some(perturbed) if perturbed.dot(&normal) > 0.0 => perturbed,
+            _ => normal,
+        }
+    }
+}
+
+/// Brightness of a color in the range 0.0 to 1.0
+fn luminance(color: Color) -> f64 {
+    (0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z) / 255.0
+}
+
+fn height_gradient(procedural: &Procedural, point: Point) -> Normal {
+    let epsilon = 1e-3;
+    let height = |offset: Point| luminance(procedural.color_at(point + offset));
+
+    Normal::new(
+        height(Point::new(epsilon, 0.0, 0.0)) - height(Point::new(-epsilon, 0.0, 0.0)),
+        height(Point::new(0.0, epsilon, 0.0)) - height(Point::new(0.0, -epsilon, 0.0)),
+        height(Point::new(0.0, 0.0, epsilon)) - height(Point::new(0.0, 0.0, -epsilon)),
+    ) / (2.0 * epsilon)
+}
@@ -0,0 +1,69 @@
+use super::Image;
+use crate::type_aliases::Color;
+use gdk_pixbuf::{Colorspace, Pixbuf};
+use std::io;
+
+/// Decode an image with gdk-pixbuf, which handles PNG, JPEG and most other formats
+pub fn read_image(path: &str) -> io::Result<Image> {
+    let pixbuf = Pixbuf::from_file(path)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+    // SAFETY: the pixbuf was just created and is not shared, so nothing else writes to the
+    // pixels while they are copied
+    let bytes = unsafe { pixbuf.get_pixels() };
+
+    let width = pixbuf.get_width() as usize;
+    let height = pixbuf.get_height() as usize;
+    let channels = pixbuf.get_n_channels() as usize;
+    let rowstride = pixbuf.get_rowstride() as usize;
+
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| y * rowstride + x * channels))
+        .map(|offset| {
+            Color::new(
+                bytes[offset] as f64,
+                bytes[offset + 1] as f64,
+                bytes[offset + 2] as f64,
+            )
+        })
+        .collect();
+
+    Ok(Image {
+        width: width as u32,
+        height: height as u32,
+        pixels,
+    })
+}
+
+/// Encode an image with gdk-pixbuf, in the format given by the extension of `path`
+pub fn write_image(path: &str, image: &Image) -> io::Result<()> {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("png")
+        .to_lowercase();
+    let format = if extension == "jpg" {
+        "jpeg"
+    } else {
+        &extension
+    };
+
+    let bytes: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|color| [color.x, color.y, color.z])
+        .map(|channel| channel.round().clamp(0.0, 255.0) as u8)
+        .collect();
+    let pixbuf = Pixbuf::from_mut_slice(
+        bytes,
+        Colorspace::Rgb,
+        false,
+        8,
+        image.width as i32,
+        image.height as i32,
+        image.width as i32 * 3,
+    );
+
+    pixbuf
+        .savev(path, format, &[])
+        .map_err(|err| io::Error::other(err.to_string()))
+}
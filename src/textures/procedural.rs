@@ -0,0 +1,158 @@
+use crate::type_aliases::{Color, Point};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::sync::OnceLock;
+
+/// The patterns a `Procedural` texture can have
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    Checkerboard,
+    Noise,
+    Marble,
+    Wood,
+}
+
+/// `Procedural` is a diffusive texture where the color depends on the position on the object.
+///
+/// The pattern blends between `color_a` and `color_b`. `scale` is the frequency of the pattern,
+/// so higher values give smaller checkers, finer noise and more rings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Procedural {
+    pub pattern: Pattern,
+    pub scale: f64,
+    pub color_a: Color,
+    pub color_b: Color,
+}
+
+impl Procedural {
+    pub fn new(pattern: Pattern, scale: f64, color_a: Color, color_b: Color) -> Self {
+        Self {
+            pattern,
+            scale,
+            color_a,
+            color_b,
+        }
+    }
+
+    /// The color at `point`, given in the coordinates of the object
+    pub fn color_at(&self, point: Point) -> Color {
+        let point = point * self.scale;
+
+        let t = match self.pattern {
+            Pattern::Checkerboard => {
+                // Small offset so surfaces lying exactly on a cell border do not flicker
+                let cell = point.map(|v| (v + 1e-4).floor() as i64);
+                ((cell.x + cell.y + cell.z).rem_euclid(2)) as f64
+            }
+            Pattern::Noise => 0.5 * (1.0 + perlin_noise(point)),
+            Pattern::Marble => 0.5 * (1.0 + (point.x + 5.0 * turbulence(point, 7)).sin()),
+            Pattern::Wood => {
+                let distance = (point.x * point.x + point.z * point.z).sqrt();
+                (distance + 0.3 * turbulence(point, 3)).fract()
+            }
+        };
+
+        self.color_a * (1.0 - t) + self.color_b * t
+    }
+}
+
+/// Sum of noise at increasing frequencies and decreasing strength
+pub fn turbulence(point: Point, octaves: u32) -> f64 {
+    let mut total = 0.0;
+    let mut point = point;
+    let mut weight = 1.0;
+
+    for _ in 0..octaves {
+        total += weight * perlin_noise(point).abs();
+        weight *= 0.5;
+        point *= 2.0;
+    }
+    total
+}
+
+/// [Improved Perlin noise](https://mrl.cs.nyu.edu/~perlin/noise/) in the range -1.0 to 1.0
+pub fn perlin_noise(point: Point) -> f64 {
+    let permutation = permutation();
+    let cell = point.map(|v| v.floor());
+    let [x, y, z] = [cell.x, cell.y, cell.z].map(|v| (v as i64).rem_euclid(256) as usize);
+    let local = point - cell;
+    let [u, v, w] = [local.x, local.y, local.z].map(fade);
+
+    // Hash the corners of the cell
+    let a = permutation[x] as usize + y;
+    let aa = permutation[a] as usize + z;
+    let ab = permutation[a + 1] as usize + z;
+    let b = permutation[x + 1] as usize + y;
+    let ba = permutation[b] as usize + z;
+    let bb = permutation[b + 1] as usize + z;
+
+    let (lx, ly, lz) = (local.x, local.y, local.z);
+    lerp(
+        w,
+        lerp(
+            v,
+            lerp(
+                u,
+                gradient(permutation[aa], lx, ly, lz),
+                gradient(permutation[ba], lx - 1.0, ly, lz),
+            ),
+            lerp(
+                u,
+                gradient(permutation[ab], lx, ly - 1.0, lz),
+                gradient(permutation[bb], lx - 1.0, ly - 1.0, lz),
+            ),
+        ),
+        lerp(
+            v,
+            lerp(
+                u,
+                gradient(permutation[aa + 1], lx, ly, lz - 1.0),
+                gradient(permutation[ba + 1], lx - 1.0, ly, lz - 1.0),
+            ),
+            lerp(
+                u,
+                gradient(permutation[ab + 1], lx, ly - 1.0, lz - 1.0),
+                gradient(permutation[bb + 1], lx - 1.0, ly - 1.0, lz - 1.0),
+            ),
+        ),
+    )
+}
+
+/// Shuffled table of 0..256, repeated twice to avoid wrapping the indices. The seed is fixed so
+/// the noise looks the same in every render.
+fn permutation() -> &'static [u8; 512] {
+    static PERMUTATION: OnceLock<[u8; 512]> = OnceLock::new();
+    PERMUTATION.get_or_init(|| {
+        let mut values: Vec<u8> = (0..=255).collect();
+        values.shuffle(&mut StdRng::seed_from_u64(2023));
+
+        let mut permutation = [0; 512];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = values[i % 256];
+        }
+        permutation
+    })
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product of the offset with one of 12 gradient directions picked by `hash`
+fn gradient(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}